@@ -0,0 +1,21 @@
+//! 只使用纯同步的编码、解码与解析，不依赖 async 运行时与 HTTP
+//!
+//! 同样可以编译到 wasm32-unknown-unknown：
+//!
+//! ```sh
+//! cargo build --example codec --no-default-features --target wasm32-unknown-unknown
+//! ```
+
+use get_data_url::{DataUrl, EncodingStrategy};
+
+fn main() {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"/>"#;
+    let media_type = "image/svg+xml";
+    let base64_encoded = EncodingStrategy::Auto.use_base64(media_type);
+    let data_url = DataUrl::new(media_type, svg.to_vec(), base64_encoded);
+    println!("{}", data_url);
+
+    let parsed = DataUrl::parse(&data_url.to_string()).expect("刚生成的 Data URL 应能解析");
+    assert_eq!(parsed.data, svg);
+    println!("{}", parsed.canonical_string());
+}
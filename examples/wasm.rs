@@ -0,0 +1,44 @@
+//! 获取资源并内联为 Data URL；编译到 wasm32-unknown-unknown 时请求经由浏览器的 fetch 发出
+//!
+//! ```sh
+//! cargo build --example wasm --target wasm32-unknown-unknown
+//! cargo run --example wasm -- https://www.rust-lang.org/favicon.ico
+//! ```
+//!
+//! wasm32 上跨域请求受 CORS 限制；代理、连接池、重定向策略、按主机限制等选项由浏览器控制，
+//! 只在原生目标上提供，`blocking` feature 也不可用
+
+use std::time::Duration;
+
+use get_data_url::{DataUrlError, GetDataUrl};
+
+async fn inline(url: &str) -> Result<String, DataUrlError> {
+    let data_url = GetDataUrl::new()
+        .with_retries(2)
+        .with_read_timeout(Duration::from_secs(10))
+        .fetch(url)
+        .await?;
+    Ok(data_url.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    // 浏览器中没有阻塞等待，交给事件循环执行；实际使用时可将结果写入 DOM
+    wasm_bindgen_futures::spawn_local(async {
+        if let Err(e) = inline("/favicon.ico").await {
+            panic!("获取失败: {}", e);
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://www.rust-lang.org/favicon.ico".to_string());
+    match inline(&url).await {
+        Ok(data_url) => println!("{}", data_url),
+        Err(e) => eprintln!("获取失败: {}", e),
+    }
+}
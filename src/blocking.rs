@@ -0,0 +1,226 @@
+//! 基于 `reqwest::blocking` 的同步 API，需要启用 `blocking` feature
+
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use reqwest::blocking::{Client, Response};
+
+use crate::client::{check_size, check_status, content_type};
+use crate::{
+    DEFAULT_MAX_SIZE, DEFAULT_MEDIA_TYPE, DataUrl, DataUrlError, EncodingStrategy, RedirectPolicy,
+};
+
+/// 同步的 HTTP 到 Data URL 转换器
+#[derive(Debug, Clone)]
+pub struct GetDataUrl {
+    client: Client,
+    max_size: Option<usize>,
+    timeout: Option<Duration>,
+    encoding: EncodingStrategy,
+    accept_status: bool,
+}
+
+impl Default for GetDataUrl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GetDataUrl {
+    /// 创建一个新的转换器实例
+    pub fn new() -> Self {
+        let client = Client::builder()
+            .redirect(RedirectPolicy::default().into())
+            .build()
+            .expect("failed to build HTTP client");
+        Self::with_client(client)
+    }
+
+    /// 使用自定义 HTTP 客户端创建转换器实例
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            max_size: Some(DEFAULT_MAX_SIZE),
+            timeout: None,
+            encoding: EncodingStrategy::default(),
+            accept_status: false,
+        }
+    }
+
+    /// 设置是否接受 4xx/5xx 响应并将其响应体转换为 DataUrl
+    ///
+    /// 默认关闭，此时错误状态码返回 [`DataUrlError::Status`]
+    pub fn with_accept_status(mut self, accept_status: bool) -> Self {
+        self.accept_status = accept_status;
+        self
+    }
+
+    /// 设置数据编码策略，默认为 [`EncodingStrategy::AlwaysBase64`]
+    pub fn with_encoding(mut self, encoding: EncodingStrategy) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// 设置单次请求的超时时间，超时后返回 [`DataUrlError::Timeout`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 设置最大下载大小 (字节)，默认为 [`DEFAULT_MAX_SIZE`]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// 从 URL 获取资源并转换为 DataUrl
+    pub fn fetch(&self, url: &str) -> Result<DataUrl, DataUrlError> {
+        let mut request = self.client.get(url);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        let response = request.send()?;
+        debug!("GET {} -> {}", url, response.status());
+        check_status(self.accept_status, response.status())?;
+        self.response_to_data_url(response)
+    }
+
+    /// 将 HTTP 响应转换为 DataUrl
+    pub fn response_to_data_url(&self, response: Response) -> Result<DataUrl, DataUrlError> {
+        // 获取内容类型
+        let content_type =
+            content_type(response.headers()).unwrap_or_else(|| DEFAULT_MEDIA_TYPE.to_string());
+
+        // 读取响应字节
+        let bytes = self.read_body(response)?;
+
+        let base64_encoded = self.encoding.use_base64(&content_type);
+        Ok(DataUrl::new(content_type, bytes, base64_encoded))
+    }
+
+    /// 读取本地文件并转换为 DataUrl，媒体类型根据扩展名推断
+    pub fn from_path(&self, path: impl AsRef<Path>) -> Result<DataUrl, DataUrlError> {
+        let path = path.as_ref();
+
+        check_size(self.max_size, std::fs::metadata(path)?.len())?;
+
+        let bytes = std::fs::read(path)?;
+        let content_type = mime_guess::from_path(path)
+            .first_or_octet_stream()
+            .to_string();
+
+        let base64_encoded = self.encoding.use_base64(&content_type);
+        Ok(DataUrl::new(content_type, bytes, base64_encoded))
+    }
+
+    /// 读取响应体，并在读取过程中检查大小限制
+    fn read_body(&self, response: Response) -> Result<Vec<u8>, DataUrlError> {
+        let Some(limit) = self.max_size else {
+            return Ok(response.bytes()?.to_vec());
+        };
+
+        // 优先根据 Content-Length 提前拒绝
+        if let Some(length) = response.content_length() {
+            check_size(Some(limit), length)?;
+        }
+
+        // 最多多读一个字节，用于判断是否超出限制
+        let mut body = Vec::new();
+        response.take(limit as u64 + 1).read_to_end(&mut body)?;
+        check_size(Some(limit), body.len() as u64)?;
+        Ok(body)
+    }
+}
+
+/// 便捷函数：从 URL 获取资源并转换为 Data URL 字符串
+pub fn url_to_data_url(url: &str) -> Result<String, DataUrlError> {
+    let converter = GetDataUrl::new();
+    let data_url = converter.fetch(url)?;
+    Ok(data_url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // 阻塞客户端不能在异步运行时线程中使用，因此放入 spawn_blocking 中执行
+
+    #[tokio::test]
+    async fn test_fetch_data_url() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let uri = mock_server.uri();
+        let data_url = tokio::task::spawn_blocking(move || GetDataUrl::new().fetch(&uri))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(data_url.base64_encoded);
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_url_to_data_url_convenience() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let uri = mock_server.uri();
+        let data_url_str = tokio::task::spawn_blocking(move || url_to_data_url(&uri))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(data_url_str, "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ==");
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let uri = mock_server.uri();
+        let result = tokio::task::spawn_blocking(move || GetDataUrl::new().fetch(&uri))
+            .await
+            .unwrap();
+
+        assert!(matches!(result, Err(DataUrlError::Status { code: 404 })));
+    }
+
+    #[tokio::test]
+    async fn test_max_size_exceeded() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 4096]))
+            .mount(&mock_server)
+            .await;
+
+        let uri = mock_server.uri();
+        let result =
+            tokio::task::spawn_blocking(move || GetDataUrl::new().with_max_size(1024).fetch(&uri))
+                .await
+                .unwrap();
+
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge { limit: 1024, .. })
+        ));
+    }
+}
@@ -0,0 +1,456 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::Proxy;
+
+use crate::client::{Auth, Config, allowed_types};
+use crate::{Cache, ContentTypeResolver, DataUrlError, EncodingStrategy, GetDataUrl, UrlPolicy};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{HostLimit, HostLimiter, RedirectPolicy};
+
+/// 默认的 TCP keepalive 间隔，与 reqwest 一致
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// [`GetDataUrl`] 构建器，用于组合各项配置并创建底层 HTTP 客户端
+///
+/// wasm32 上请求经由浏览器的 fetch 发出，代理、连接池、重定向、解压等由浏览器控制，
+/// 相应的选项只在原生目标上提供
+#[derive(Debug, Clone)]
+pub struct GetDataUrlBuilder {
+    config: Config,
+    #[cfg(not(target_arch = "wasm32"))]
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    proxy: Option<String>,
+    single_flight: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    host_limit: Option<HostLimit>,
+    #[cfg(not(target_arch = "wasm32"))]
+    http2_prior_knowledge: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_max_idle_per_host: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    tcp_keepalive: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    auto_decompress: bool,
+}
+
+impl Default for GetDataUrlBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GetDataUrlBuilder {
+    /// 创建一个使用默认配置的构建器
+    pub fn new() -> Self {
+        Self {
+            config: Config::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            connect_timeout: None,
+            user_agent: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy: None,
+            single_flight: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            host_limit: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            http2_prior_knowledge: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_max_idle_per_host: usize::MAX,
+            #[cfg(not(target_arch = "wasm32"))]
+            tcp_keepalive: Some(DEFAULT_TCP_KEEPALIVE),
+            #[cfg(not(target_arch = "wasm32"))]
+            auto_decompress: true,
+        }
+    }
+
+    /// 设置最大下载大小 (字节)，默认为 [`DEFAULT_MAX_SIZE`](crate::DEFAULT_MAX_SIZE)
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.config.max_size = Some(max_size);
+        self
+    }
+
+    /// 设置单次请求的总超时时间，默认不超时
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// 设置等待响应头的超时时间，见 [`GetDataUrl::with_read_timeout`]
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置建立连接 (含 TLS 握手) 的超时时间，超时后返回 [`DataUrlError::ConnectTimeout`]，默认不超时
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置数据编码策略，默认为 [`EncodingStrategy::AlwaysBase64`]
+    pub fn encoding(mut self, encoding: EncodingStrategy) -> Self {
+        self.config.encoding = encoding;
+        self
+    }
+
+    /// 设置最大重试次数，默认不重试
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.config.retries = retries;
+        self
+    }
+
+    /// 设置重试的基础延迟，默认为 [`DEFAULT_RETRY_DELAY`](crate::DEFAULT_RETRY_DELAY)
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.config.retry_delay = delay;
+        self
+    }
+
+    /// 设置按 `Retry-After` 等待的最长时间，见 [`GetDataUrl::with_max_retry_after`]
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.config.max_retry_after = max;
+        self
+    }
+
+    /// 设置重定向策略，默认为 [`RedirectPolicy::Limited(10)`](RedirectPolicy::Limited)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn redirect_policy(mut self, redirect: RedirectPolicy) -> Self {
+        self.config.redirect = redirect;
+        self
+    }
+
+    /// 设置是否启用内容嗅探，默认关闭
+    pub fn sniffing(mut self, sniffing: bool) -> Self {
+        self.config.sniffing = sniffing;
+        self
+    }
+
+    /// 设置批量获取时的最大并发数，默认为 [`DEFAULT_CONCURRENCY`](crate::DEFAULT_CONCURRENCY)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.config.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 设置每次批量获取的总字节预算，见 [`GetDataUrl::with_total_byte_budget`]
+    pub fn total_byte_budget(mut self, budget: usize) -> Self {
+        self.config.total_byte_budget = Some(budget);
+        self
+    }
+
+    /// 设置请求的 User-Agent，默认使用 reqwest 的设置
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// 设置是否接受 4xx/5xx 响应，默认关闭
+    pub fn accept_status(mut self, accept_status: bool) -> Self {
+        self.config.accept_status = accept_status;
+        self
+    }
+
+    /// 设置渲染后 Data URL 字符串的最大长度 (字符数)，默认不限制
+    pub fn max_encoded_len(mut self, max_encoded_len: usize) -> Self {
+        self.config.max_encoded_len = Some(max_encoded_len);
+        self
+    }
+
+    /// 为所有请求附加 HTTP Basic 认证
+    pub fn basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: Option<impl Into<String>>,
+    ) -> Self {
+        self.config.auth = Some(Auth::Basic {
+            username: username.into(),
+            password: password.map(Into::into),
+        });
+        self
+    }
+
+    /// 为所有请求附加 Bearer Token 认证
+    pub fn bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.config.auth = Some(Auth::Bearer(token.into()));
+        self
+    }
+
+    /// 设置是否去掉媒体类型中的所有参数 (如 charset)，默认保留
+    pub fn strip_params(mut self, strip_params: bool) -> Self {
+        self.config.strip_params = strip_params;
+        self
+    }
+
+    /// 设置 URL 访问策略，默认只允许 `http` 和 `https`
+    pub fn url_policy(mut self, url_policy: UrlPolicy) -> Self {
+        self.config.url_policy = url_policy;
+        self
+    }
+
+    /// 启用响应缓存，见 [`GetDataUrl::with_cache`]
+    pub fn cache(mut self, cache: Arc<dyn Cache>) -> Self {
+        self.config.cache = Some(cache);
+        self
+    }
+
+    /// 设置内联阈值 (字节)，见 [`GetDataUrl::with_inline_threshold`]
+    pub fn inline_threshold(mut self, threshold: usize) -> Self {
+        self.config.inline_threshold = Some(threshold);
+        self
+    }
+
+    /// 按主机限制并发数与请求间隔，见 [`GetDataUrl::with_host_limit`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn host_limit(mut self, limit: HostLimit) -> Self {
+        self.host_limit = Some(limit);
+        self
+    }
+
+    /// 只接受指定的媒体类型，见 [`GetDataUrl::with_allowed_types`]
+    pub fn allowed_types(mut self, types: &[&str]) -> Self {
+        self.config.allowed_types = Some(allowed_types(types));
+        self
+    }
+
+    /// 设置媒体类型的判定顺序，见 [`GetDataUrl::with_content_type_resolver`]
+    pub fn content_type_resolver(mut self, resolver: ContentTypeResolver) -> Self {
+        self.config.content_type_resolver = Some(resolver);
+        self
+    }
+
+    /// 设置无法确定媒体类型时使用的类型，默认为 [`DEFAULT_MEDIA_TYPE`](crate::DEFAULT_MEDIA_TYPE)
+    pub fn default_media_type(mut self, media_type: impl Into<String>) -> Self {
+        self.config.default_media_type = media_type.into();
+        self
+    }
+
+    /// 设置是否合并对同一 URL 的并发请求，见 [`GetDataUrl::with_single_flight`]
+    pub fn single_flight(mut self, single_flight: bool) -> Self {
+        self.single_flight = single_flight;
+        self
+    }
+
+    /// 只使用 HTTP/2 (不经协商直接发送 HTTP/2 请求)，默认关闭
+    ///
+    /// 适合已知服务器支持 HTTP/2 的场景，例如对同一主机的大量并发请求可复用单个连接
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// 设置连接池中每个主机最多保留的空闲连接数，默认不限制
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// 设置 TCP keepalive 间隔，传入 `None` 关闭，默认为 15 秒
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tcp_keepalive(mut self, keepalive: impl Into<Option<Duration>>) -> Self {
+        self.tcp_keepalive = keepalive.into();
+        self
+    }
+
+    /// 设置是否发送 `Accept-Encoding` 并自动解压响应，见 [`GetDataUrl::with_auto_decompress`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn auto_decompress(mut self, auto_decompress: bool) -> Self {
+        self.auto_decompress = auto_decompress;
+        self
+    }
+
+    /// 设置代理服务器，所有请求都经由该代理发送
+    ///
+    /// 支持 `http://` 与 `https://` 代理，启用 `socks` feature 后还支持 `socks5://`；
+    /// 代理地址无效时 [`build`](Self::build) 返回错误
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// 创建底层 HTTP 客户端并构建转换器
+    pub fn build(self) -> Result<GetDataUrl, DataUrlError> {
+        let mut client = Client::builder();
+        if let Some(user_agent) = self.user_agent {
+            client = client.user_agent(user_agent);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            client = client
+                .redirect(self.config.redirect.into())
+                .pool_max_idle_per_host(self.pool_max_idle_per_host)
+                .tcp_keepalive(self.tcp_keepalive)
+                .gzip(self.auto_decompress)
+                .brotli(self.auto_decompress)
+                .deflate(self.auto_decompress);
+            if self.http2_prior_knowledge {
+                client = client.http2_prior_knowledge();
+            }
+            if let Some(timeout) = self.connect_timeout {
+                client = client.connect_timeout(timeout);
+            }
+            if let Some(proxy) = self.proxy {
+                client = client.proxy(Proxy::all(proxy)?);
+            }
+        }
+
+        let mut config = self.config;
+        config.single_flight = self.single_flight.then(Default::default);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            config.host_limiter = self
+                .host_limit
+                .map(|limit| Arc::new(HostLimiter::new(limit)));
+        }
+        Ok(GetDataUrl {
+            client: Arc::new(client.build()?),
+            config: Arc::new(config),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_builder() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(header("User-Agent", "get-data-url-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::builder()
+            .user_agent("get-data-url-test")
+            .encoding(EncodingStrategy::Auto)
+            .max_size(1024)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+
+        assert_eq!(data_url.to_string(), "data:text/plain,Hello%2C%20World%21");
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        // 未设置的选项与直接创建的实例一致
+        let built = GetDataUrl::builder().build().unwrap();
+        assert_eq!(
+            format!("{:?}", built.config),
+            format!("{:?}", GetDataUrl::new().config)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_builder_proxy() {
+        let proxy = MockServer::start().await;
+
+        // 经由 HTTP 代理的请求会发送到代理服务器
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("via proxy"))
+            .mount(&proxy)
+            .await;
+
+        let converter = GetDataUrl::builder().proxy(proxy.uri()).build().unwrap();
+        let data_url = converter
+            .fetch("http://example.invalid/asset")
+            .await
+            .unwrap();
+
+        assert_eq!(data_url.data, b"via proxy");
+    }
+
+    #[test]
+    fn test_builder_invalid_proxy() {
+        let result = GetDataUrl::builder().proxy("not a proxy url").build();
+        assert!(matches!(result, Err(DataUrlError::Http(_))));
+    }
+
+    #[tokio::test]
+    async fn test_builder_connection_reuse() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // 记录连接数的 HTTP/1.1 keep-alive 服务器
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    while matches!(socket.read(&mut request).await, Ok(n) if n > 0) {
+                        let response = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let converter = GetDataUrl::builder()
+            .pool_max_idle_per_host(1)
+            .tcp_keepalive(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let url = format!("http://{}", addr);
+        for _ in 0..2 {
+            assert_eq!(converter.fetch(&url).await.unwrap().data, b"hello");
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_builder_http2_prior_knowledge() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let (_, meta) = GetDataUrl::builder()
+            .http2_prior_knowledge()
+            .build()
+            .unwrap()
+            .fetch_with_meta(mock_server.uri())
+            .await
+            .unwrap();
+        assert_eq!(meta.status, 200);
+    }
+
+    #[tokio::test]
+    async fn test_builder_max_size() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 10]))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::builder().max_size(5).build().unwrap();
+        let result = converter.fetch(&mock_server.uri()).await;
+
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge { limit: 5, .. })
+        ));
+    }
+}
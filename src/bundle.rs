@@ -0,0 +1,123 @@
+use std::fmt::Write as _;
+
+use crate::DataUrl;
+
+/// 分隔符前缀，包含 base64 字母表以外的 `_`，不会与编码后的数据冲突
+const BOUNDARY: &str = "get-data-url_boundary";
+
+/// 将多个 DataUrl 合并为一个 MIME `multipart/related` 文档，适合作为邮件正文或归档
+///
+/// 输出包含 `MIME-Version` 与 `Content-Type` 头部；每个部分带有自身的 `Content-Type`、
+/// `Content-Transfer-Encoding: base64` 以及按顺序编号的 `Content-ID` (`<part0>`、`<part1>` ...)，
+/// 数据每 76 个字符换行。片段标识符不属于数据，会被忽略；媒体类型中的换行会被替换为空格，
+/// 以免写入额外的头部
+pub fn bundle(parts: &[DataUrl]) -> String {
+    let boundary = boundary(parts);
+    let mut document = format!(
+        "MIME-Version: 1.0\r\nContent-Type: multipart/related; boundary=\"{}\"\r\n\r\n",
+        boundary
+    );
+    for (index, part) in parts.iter().enumerate() {
+        // 百分号编码的部分同样以 base64 传输
+        let payload = part
+            .to_base64()
+            .render()
+            .line_wrap(Some(76))
+            .encoded_payload();
+        let _ = write!(
+            document,
+            "--{}\r\nContent-Type: {}\r\nContent-Transfer-Encoding: base64\r\nContent-ID: <part{}>\r\n\r\n{}\r\n",
+            boundary,
+            header_value(&part.media_type),
+            index,
+            payload
+        );
+    }
+    let _ = write!(document, "--{}--\r\n", boundary);
+    document
+}
+
+/// 将 CR、LF 替换为空格，保证头部的值只占一行
+fn header_value(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// 选择不出现在任何媒体类型中的分隔符
+fn boundary(parts: &[DataUrl]) -> String {
+    (0..)
+        .map(|n| match n {
+            0 => BOUNDARY.to_string(),
+            n => format!("{}_{}", BOUNDARY, n),
+        })
+        .find(|boundary| !parts.iter().any(|part| part.media_type.contains(boundary)))
+        .expect("总能找到可用的分隔符")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mailparse::{MailHeaderMap as _, ParsedMail};
+
+    /// 按 RFC 2046 解析文档，返回各个部分
+    fn parse(document: &str) -> ParsedMail<'_> {
+        let mail = mailparse::parse_mail(document.as_bytes()).unwrap();
+        assert_eq!(mail.ctype.mimetype, "multipart/related");
+        mail
+    }
+
+    #[test]
+    fn test_bundle() {
+        let parts = [
+            DataUrl::from_text("<img src=\"cid:part1\">", "text/html", Some("utf-8")),
+            DataUrl::new("image/png", (0..=255).collect(), true),
+        ];
+        let document = bundle(&parts);
+        assert!(document.starts_with("MIME-Version: 1.0\r\n"));
+        assert!(document.lines().all(|line| line.len() <= 78));
+
+        let mail = parse(&document);
+        assert_eq!(
+            mail.headers.get_first_value("MIME-Version").as_deref(),
+            Some("1.0")
+        );
+        assert_eq!(mail.subparts.len(), parts.len());
+        for (index, (parsed, part)) in mail.subparts.iter().zip(&parts).enumerate() {
+            let header = |name: &str| parsed.headers.get_first_value(name);
+            assert_eq!(header("Content-Type"), Some(part.media_type.clone()));
+            assert_eq!(
+                header("Content-Transfer-Encoding").as_deref(),
+                Some("base64")
+            );
+            assert_eq!(header("Content-ID"), Some(format!("<part{}>", index)));
+            assert_eq!(parsed.get_body_raw().unwrap(), part.data);
+        }
+    }
+
+    #[test]
+    fn test_boundary_collision() {
+        let part = DataUrl::new("text/plain;x=get-data-url_boundary", b"Hi".to_vec(), true);
+        let parts = std::slice::from_ref(&part);
+        assert_eq!(boundary(parts), "get-data-url_boundary_1");
+
+        let document = bundle(parts);
+        let mail = parse(&document);
+        assert_eq!(mail.subparts.len(), 1);
+        assert_eq!(mail.subparts[0].get_body_raw().unwrap(), part.data);
+    }
+
+    #[test]
+    fn test_header_injection() {
+        let part = DataUrl::new("text/plain\r\nX-Injected: yes\n", b"Hi".to_vec(), true);
+        let document = bundle(std::slice::from_ref(&part));
+
+        let mail = parse(&document);
+        let parsed = &mail.subparts[0];
+        assert_eq!(parsed.headers.get_first_value("X-Injected"), None);
+        assert_eq!(parsed.headers.get_all_headers("Content-Type").len(), 1);
+        assert_eq!(
+            parsed.headers.get_first_value("Content-ID").as_deref(),
+            Some("<part0>")
+        );
+        assert_eq!(parsed.get_body_raw().unwrap(), part.data);
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::DataUrl;
+
+/// 缓存的响应，包含用于条件请求的验证信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// 上次获取到的 DataUrl
+    pub data_url: DataUrl,
+    /// 响应的 `ETag`，下次请求时作为 `If-None-Match` 发送
+    pub etag: Option<String>,
+    /// 响应的 `Last-Modified`，下次请求时作为 `If-Modified-Since` 发送
+    pub last_modified: Option<String>,
+}
+
+/// 以 URL 为键的响应缓存，通过 [`GetDataUrl::with_cache`] 启用
+///
+/// 服务器返回 `304 Not Modified` 时直接使用缓存的 DataUrl。
+/// 只有带 `ETag` 或 `Last-Modified` 的成功响应会被缓存
+///
+/// [`GetDataUrl::with_cache`]: crate::GetDataUrl::with_cache
+pub trait Cache: Send + Sync {
+    /// 获取 URL 对应的缓存
+    fn get(&self, url: &str) -> Option<CacheEntry>;
+
+    /// 保存 URL 对应的缓存
+    fn put(&self, url: &str, entry: CacheEntry);
+}
+
+impl std::fmt::Debug for dyn Cache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("dyn Cache")
+    }
+}
+
+/// 基于 `HashMap` 的内存缓存，条目不会过期
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl MemoryCache {
+    /// 创建空缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清空所有缓存
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, url: &str) -> Option<CacheEntry> {
+        self.entries.lock().unwrap().get(url).cloned()
+    }
+
+    fn put(&self, url: &str, entry: CacheEntry) {
+        self.entries.lock().unwrap().insert(url.to_string(), entry);
+    }
+}
@@ -0,0 +1,3387 @@
+//! 基于 reqwest 的异步 HTTP 获取，需要启用默认的 `http` feature
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{Stream, StreamExt as _, stream};
+use mime::Mime;
+use reqwest::header::{
+    ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, LOCATION, RETRY_AFTER,
+};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use tokio::io::{AsyncRead, AsyncReadExt as _};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::HostLimit;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::{BlockedRedirect, RedirectChain};
+use crate::{
+    AssetRef, Cache, CacheEntry, ContentTypeResolver, ContentTypeSource, DEFAULT_MEDIA_TYPE,
+    DataUrl, DataUrlError, EncodingStrategy, FetchMeta, GetDataUrlBuilder, HostLimiter, IntoUrl,
+    SingleFlight, Source, UrlPolicy, ValidationReport, essence, is_text_mime, params, sniff,
+};
+
+/// 重定向策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// 不跟随重定向，收到 3xx 响应时返回 [`DataUrlError::Redirect`]
+    None,
+    /// 最多跟随指定次数的重定向
+    Limited(u32),
+}
+
+/// 默认最多跟随 10 次重定向，与 reqwest 一致
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Limited(10)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    /// 发送请求期间有效的 URL 访问策略，供重定向策略检查每一跳
+    static URL_POLICY: UrlPolicy;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<RedirectPolicy> for reqwest::redirect::Policy {
+    fn from(policy: RedirectPolicy) -> Self {
+        match policy {
+            RedirectPolicy::None => Self::none(),
+            // 记录经过的每一跳，超出次数或再次访问同一 URL 时返回完整的重定向链；
+            // 由 GetDataUrl 发出的请求还要求每个重定向目标都符合 URL 访问策略
+            RedirectPolicy::Limited(max) => Self::custom(move |attempt| {
+                let looped = attempt.previous().contains(attempt.url());
+                if looped || attempt.previous().len() > max as usize {
+                    let chain = attempt
+                        .previous()
+                        .iter()
+                        .chain([attempt.url()])
+                        .map(Url::to_string)
+                        .collect();
+                    attempt.error(RedirectChain(chain))
+                } else if URL_POLICY
+                    .try_with(|policy| policy.check(attempt.url()).is_err())
+                    .unwrap_or(false)
+                {
+                    let url = attempt.url().to_string();
+                    attempt.error(BlockedRedirect(url))
+                } else {
+                    attempt.follow()
+                }
+            }),
+        }
+    }
+}
+
+/// 请求认证方式，调试输出中不会包含凭据
+#[derive(Clone)]
+pub(crate) enum Auth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer(String),
+}
+
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Self::Bearer(_) => f.debug_tuple("Bearer").field(&"***").finish(),
+        }
+    }
+}
+
+impl Auth {
+    /// 为请求附加 Authorization 请求头
+    fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Self::Basic { username, password } => request.basic_auth(username, password.as_ref()),
+            Self::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+/// UTF-8 字节序标记
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 默认的最大下载大小 (50 MB)
+pub const DEFAULT_MAX_SIZE: usize = 50 * 1024 * 1024;
+
+/// 默认的重试基础延迟
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 按 `Retry-After` 等待时默认的最长时间
+pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// 批量获取时默认的最大并发数
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// HTTP 到 Data URL 转换器
+///
+/// 复制的开销很小：副本共享同一个 HTTP 客户端 (连接池) 与配置，
+/// 对副本调用 `with_*` 修改配置时才会复制配置，不影响原实例
+///
+/// 同样支持 wasm32-unknown-unknown：请求经由浏览器的 fetch 发出，重定向、解压、代理等由浏览器处理，
+/// 相应的选项与按主机限制只在原生目标上提供
+#[derive(Debug, Clone)]
+pub struct GetDataUrl {
+    pub(crate) client: Arc<Client>,
+    pub(crate) config: Arc<Config>,
+}
+
+/// 转换器的全部配置，在副本之间共享
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    pub(crate) max_size: Option<usize>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) encoding: EncodingStrategy,
+    pub(crate) retries: u32,
+    pub(crate) retry_delay: Duration,
+    pub(crate) max_retry_after: Duration,
+    pub(crate) redirect: RedirectPolicy,
+    pub(crate) sniffing: bool,
+    pub(crate) concurrency: usize,
+    pub(crate) accept_status: bool,
+    pub(crate) max_encoded_len: Option<usize>,
+    pub(crate) auth: Option<Auth>,
+    pub(crate) strip_params: bool,
+    pub(crate) url_policy: UrlPolicy,
+    pub(crate) cache: Option<Arc<dyn Cache>>,
+    pub(crate) single_flight: Option<Arc<SingleFlight>>,
+    pub(crate) default_media_type: String,
+    pub(crate) inline_threshold: Option<usize>,
+    pub(crate) host_limiter: Option<Arc<HostLimiter>>,
+    pub(crate) allowed_types: Option<Vec<String>>,
+    pub(crate) total_byte_budget: Option<usize>,
+    pub(crate) content_type_resolver: Option<ContentTypeResolver>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_size: Some(DEFAULT_MAX_SIZE),
+            timeout: None,
+            read_timeout: None,
+            encoding: EncodingStrategy::default(),
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            max_retry_after: DEFAULT_MAX_RETRY_AFTER,
+            redirect: RedirectPolicy::default(),
+            sniffing: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            accept_status: false,
+            max_encoded_len: None,
+            auth: None,
+            strip_params: false,
+            url_policy: UrlPolicy::default(),
+            cache: None,
+            single_flight: None,
+            default_media_type: DEFAULT_MEDIA_TYPE.to_string(),
+            inline_threshold: None,
+            host_limiter: None,
+            allowed_types: None,
+            total_byte_budget: None,
+            content_type_resolver: None,
+        }
+    }
+}
+
+impl Default for GetDataUrl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GetDataUrl {
+    /// 创建一个新的转换器实例
+    pub fn new() -> Self {
+        Self::builder()
+            .build()
+            .expect("failed to build HTTP client")
+    }
+
+    /// 获取底层 HTTP 客户端，可用于发送本库以外的请求
+    ///
+    /// 复制 `Client` 的开销很小，复制后仍共享同一个连接池
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// 创建构建器以组合各项配置
+    pub fn builder() -> GetDataUrlBuilder {
+        GetDataUrlBuilder::new()
+    }
+
+    /// 使用自定义 HTTP 客户端创建转换器实例
+    ///
+    /// 重定向等行为由该客户端自身的配置决定，超出重定向次数时的错误不包含重定向链
+    pub fn with_client(client: Client) -> Self {
+        Self::with_shared_client(Arc::new(client))
+    }
+
+    /// 使用与应用其他部分共享的 HTTP 客户端创建转换器实例
+    pub fn with_shared_client(client: Arc<Client>) -> Self {
+        Self {
+            client,
+            config: Arc::new(Config::default()),
+        }
+    }
+
+    /// 启用响应缓存，再次获取同一 URL 时发送条件请求，返回 304 时使用缓存
+    ///
+    /// 缓存以 URL 为键，不区分请求头，可使用 [`MemoryCache`](crate::MemoryCache) 或自定义实现
+    pub fn with_cache(mut self, cache: Arc<dyn Cache>) -> Self {
+        self.config_mut().cache = Some(cache);
+        self
+    }
+
+    /// 设置内联阈值 (字节)，超出时返回 [`DataUrlError::TooLargeToInline`]，默认不限制
+    ///
+    /// 与 `max_size` 不同，这是"改为链接引用"的信号：调用方可以据此回退到普通 URL。
+    /// 响应提供 Content-Length 时无需下载即可判断，否则下载完成后检查
+    pub fn with_inline_threshold(mut self, threshold: usize) -> Self {
+        self.config_mut().inline_threshold = Some(threshold);
+        self
+    }
+
+    /// 按主机限制并发数与请求间隔，默认不限制
+    ///
+    /// 限制状态在转换器的副本之间共享；重新调用会重置状态
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_host_limit(mut self, limit: HostLimit) -> Self {
+        self.config_mut().host_limiter = Some(Arc::new(HostLimiter::new(limit)));
+        self
+    }
+
+    /// 只接受指定的媒体类型，其余返回 [`DataUrlError::DisallowedType`]，默认不限制
+    ///
+    /// 以 `/` 或 `/*` 结尾的项匹配该大类 (如 `image/`)，其余项须与 `type/subtype` 完全相同，均不区分大小写。
+    /// 响应带有 Content-Type 时在读取响应体之前检查，否则检查嗅探或默认的媒体类型
+    pub fn with_allowed_types(mut self, types: &[&str]) -> Self {
+        self.config_mut().allowed_types = Some(allowed_types(types));
+        self
+    }
+
+    /// 设置媒体类型的判定顺序，所有来源都没有结果时使用默认媒体类型
+    ///
+    /// 对响应、本地文件与读取器均生效；设置后 [`with_sniffing`](Self::with_sniffing) 不再起作用，
+    /// 是否嗅探由是否包含 [`ContentTypeSource::Sniff`](crate::ContentTypeSource::Sniff) 决定
+    pub fn with_content_type_resolver(mut self, resolver: ContentTypeResolver) -> Self {
+        self.config_mut().content_type_resolver = Some(resolver);
+        self
+    }
+
+    /// 设置无法确定媒体类型 (缺少 Content-Type 且未能嗅探) 时使用的类型，默认为 [`DEFAULT_MEDIA_TYPE`]
+    pub fn with_default_media_type(mut self, media_type: impl Into<String>) -> Self {
+        self.config_mut().default_media_type = media_type.into();
+        self
+    }
+
+    /// 设置是否合并对同一 URL 的并发 [`fetch`](Self::fetch) 请求，默认关闭
+    ///
+    /// 开启后同时进行的相同请求只下载一次，所有调用者得到同一结果的副本；
+    /// 多个调用者共享的错误包装为 [`DataUrlError::Shared`]；
+    /// 只在配置相同的副本之间合并，之后再调用其他 `with_*` 方法修改配置的副本不参与合并
+    pub fn with_single_flight(mut self, single_flight: bool) -> Self {
+        self.config_mut().single_flight = single_flight.then(Default::default);
+        self
+    }
+
+    /// 设置 URL 访问策略，默认只允许 `http` 和 `https`
+    ///
+    /// 违反策略的 URL 在发出请求前返回 [`DataUrlError::Blocked`]，重定向目标违反策略时同样如此
+    pub fn with_url_policy(mut self, url_policy: UrlPolicy) -> Self {
+        self.config_mut().url_policy = url_policy;
+        self
+    }
+
+    /// 设置是否去掉媒体类型中的所有参数 (如 charset)，默认保留
+    pub fn with_strip_params(mut self, strip_params: bool) -> Self {
+        self.config_mut().strip_params = strip_params;
+        self
+    }
+
+    /// 为所有请求附加 HTTP Basic 认证
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: Option<impl Into<String>>,
+    ) -> Self {
+        self.config_mut().auth = Some(Auth::Basic {
+            username: username.into(),
+            password: password.map(Into::into),
+        });
+        self
+    }
+
+    /// 为所有请求附加 Bearer Token 认证
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.config_mut().auth = Some(Auth::Bearer(token.into()));
+        self
+    }
+
+    /// 设置渲染后 Data URL 字符串的最大长度 (字符数)，默认不限制
+    ///
+    /// 与下载大小限制不同，该限制针对最终输出的字符串，base64 编码会使长度增加约三分之一
+    pub fn with_max_encoded_len(mut self, max_encoded_len: usize) -> Self {
+        self.config_mut().max_encoded_len = Some(max_encoded_len);
+        self
+    }
+
+    /// 设置是否接受 4xx/5xx 响应并将其响应体转换为 DataUrl
+    ///
+    /// 默认关闭，此时错误状态码返回 [`DataUrlError::Status`]
+    pub fn with_accept_status(mut self, accept_status: bool) -> Self {
+        self.config_mut().accept_status = accept_status;
+        self
+    }
+
+    /// 设置批量获取时的最大并发数，默认为 [`DEFAULT_CONCURRENCY`]
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.config_mut().concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 设置是否启用内容嗅探，默认关闭
+    ///
+    /// 启用后，缺少可用的 Content-Type (或文件扩展名无法识别) 时，根据数据开头的魔数推断媒体类型
+    pub fn with_sniffing(mut self, sniffing: bool) -> Self {
+        self.config_mut().sniffing = sniffing;
+        self
+    }
+
+    /// 使用指定的重定向策略创建转换器实例，默认策略为 [`RedirectPolicy::Limited(10)`](RedirectPolicy::Limited)
+    ///
+    /// wasm32 上重定向由浏览器处理，不支持此选项
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_redirect_policy(redirect: RedirectPolicy) -> Self {
+        Self::builder()
+            .redirect_policy(redirect)
+            .build()
+            .expect("failed to build HTTP client")
+    }
+
+    /// 创建转换器实例，设置是否发送 `Accept-Encoding` 并自动解压 gzip、brotli、deflate 响应，默认启用
+    ///
+    /// 关闭后得到服务器返回的原始 (可能已压缩) 字节，可通过 [`FetchMeta::content_encoding`] 得知压缩方式
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_auto_decompress(auto_decompress: bool) -> Self {
+        Self::builder()
+            .auto_decompress(auto_decompress)
+            .build()
+            .expect("failed to build HTTP client")
+    }
+
+    /// 设置最大重试次数，默认不重试
+    ///
+    /// 仅在连接错误、等待响应头超时以及 429/502/503/504 响应时重试，重试间隔按指数退避并加入随机抖动；
+    /// 429 与 503 带有 `Retry-After` 时改为按服务器要求等待，见 [`with_max_retry_after`](Self::with_max_retry_after)
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.config_mut().retries = retries;
+        self
+    }
+
+    /// 设置重试的基础延迟，默认为 [`DEFAULT_RETRY_DELAY`]
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.config_mut().retry_delay = delay;
+        self
+    }
+
+    /// 设置按 `Retry-After` 等待的最长时间，默认为 [`DEFAULT_MAX_RETRY_AFTER`]
+    ///
+    /// 服务器要求的等待时间更长时只等待该时长，避免请求长时间停滞
+    pub fn with_max_retry_after(mut self, max: Duration) -> Self {
+        self.config_mut().max_retry_after = max;
+        self
+    }
+
+    /// 设置每次批量获取 ([`fetch_many`](Self::fetch_many)、[`fetch_stream`](Self::fetch_stream))
+    /// 解码后数据的总字节预算，默认不限制
+    ///
+    /// 每个请求最多下载发出时的剩余预算，超出时不再继续下载；使累计字节数超出预算的结果
+    /// 以及之后所有尚未完成的请求都返回 [`DataUrlError::BudgetExceeded`]，正在进行的请求立即中止，
+    /// 尚未开始的请求不会发出，因此成功结果的总大小不超过预算
+    pub fn with_total_byte_budget(mut self, budget: usize) -> Self {
+        self.config_mut().total_byte_budget = Some(budget);
+        self
+    }
+
+    /// 设置数据编码策略，默认为 [`EncodingStrategy::AlwaysBase64`]
+    pub fn with_encoding(mut self, encoding: EncodingStrategy) -> Self {
+        self.config_mut().encoding = encoding;
+        self
+    }
+
+    /// 设置单次请求 (包括下载响应体) 的总超时时间，超时后返回 [`DataUrlError::Timeout`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.config_mut().timeout = Some(timeout);
+        self
+    }
+
+    /// 设置等待响应头 (首字节) 的超时时间，超时后返回 [`DataUrlError::ReadTimeout`]
+    ///
+    /// 与 [`with_timeout`](Self::with_timeout) 不同，开始接收响应后不再计时，
+    /// 因此可以区分"服务器迟迟不响应"与"响应体太大下载太慢"；每次重试单独计时
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.config_mut().read_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置最大下载大小 (字节)，默认为 [`DEFAULT_MAX_SIZE`]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.config_mut().max_size = Some(max_size);
+        self
+    }
+
+    /// 从 URL 获取资源并转换为 DataUrl
+    ///
+    /// 空响应体 (包括 204 No Content) 会得到数据为空的 DataUrl，而不是错误
+    /// 格式错误的 URL 在发出请求前返回 [`DataUrlError::InvalidUrl`]
+    pub async fn fetch(&self, url: impl IntoUrl) -> Result<DataUrl, DataUrlError> {
+        let url = url.into_url()?;
+        let Some(single_flight) = &self.config.single_flight else {
+            return self.fetch_request(url.as_str(), self.get(&url)?).await;
+        };
+
+        single_flight
+            .run(url.to_string(), || {
+                let this = self.clone();
+                Box::pin(async move { this.fetch_request(url.as_str(), this.get(&url)?).await })
+            })
+            .await
+    }
+
+    /// 获取资源，`token` 被取消时立即中止并返回 [`DataUrlError::Cancelled`]
+    ///
+    /// 所有 `fetch*` 方法都可以通过直接丢弃 future 来取消，连接与已下载的部分数据随之释放；
+    /// 本方法适合取消信号来自其他任务的场景，例如客户端断开连接
+    pub async fn fetch_cancellable(
+        &self,
+        url: impl IntoUrl,
+        token: CancellationToken,
+    ) -> Result<DataUrl, DataUrlError> {
+        token
+            .run_until_cancelled(self.fetch(url))
+            .await
+            .unwrap_or(Err(DataUrlError::Cancelled))
+    }
+
+    /// 获取资源并转换为 DataUrl，同时返回最终 URL、状态码等响应元数据
+    ///
+    /// 适合记录日志或检查重定向是否跳转到了意料之外的地址，不需要元数据时请使用 [`fetch`](Self::fetch)
+    pub async fn fetch_with_meta(
+        &self,
+        url: impl IntoUrl,
+    ) -> Result<(DataUrl, FetchMeta), DataUrlError> {
+        let url = url.into_url()?;
+        self.fetch_request_with_meta(url.as_str(), self.get(&url)?, &mut |_, _| {})
+            .await
+    }
+
+    /// 获取资源并转换为 DataUrl，每收到一块数据调用一次 `progress`
+    ///
+    /// `progress` 的参数为已下载的字节数与 Content-Length (未知时为 `None`)；
+    /// 响应经过压缩时 reqwest 不提供 Content-Length，总大小总是 `None`
+    pub async fn fetch_with_progress(
+        &self,
+        url: impl IntoUrl,
+        mut progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> Result<DataUrl, DataUrlError> {
+        let url = url.into_url()?;
+        self.fetch_request_with_meta(url.as_str(), self.get(&url)?, &mut progress)
+            .await
+            .map(|(data_url, _)| data_url)
+    }
+
+    /// 发送 HEAD 请求获取资源的媒体类型与大小，不下载响应体
+    ///
+    /// 报告的长度超出 `max_size` 时返回 [`DataUrlError::TooLarge`]；
+    /// 不支持 HEAD 的服务器通常返回 405，此时得到 [`DataUrlError::Status`]
+    pub async fn head(&self, url: impl IntoUrl) -> Result<FetchMeta, DataUrlError> {
+        let url = url.into_url()?;
+        self.config.url_policy.check(&url)?;
+        let _permit = self.acquire_host(url.as_str()).await;
+        let (response, attempts) = self.send(self.client.head(url)).await?;
+        debug!("HEAD {} -> {}", response.url(), response.status());
+        self.check_redirect(&response)?;
+        self.check_status(&response)?;
+
+        let mut meta = FetchMeta::new(&response, attempts);
+        // HEAD 响应没有响应体，长度只能从响应头读取
+        meta.content_length = header_string(response.headers(), CONTENT_LENGTH)
+            .and_then(|length| length.trim().parse().ok());
+        if let Some(length) = meta.content_length {
+            check_size(self.config.max_size, length)?;
+        }
+        Ok(meta)
+    }
+
+    /// 下载资源并检查是否可以内联，只统计大小而不保留数据
+    ///
+    /// 仅保留开头少量字节用于嗅探媒体类型，超出 `max_size` 时立即停止下载；
+    /// 大小或类型不合格 (包括不在 `allowed_types` 中) 时返回报告而不是错误，网络错误与错误状态码仍返回 `Err`
+    pub async fn validate(&self, url: impl IntoUrl) -> Result<ValidationReport, DataUrlError> {
+        let url = url.into_url()?;
+        let request = self.get(&url)?;
+        let _permit = self.acquire_host(url.as_str()).await;
+        let (response, _) = self.send(request).await?;
+        debug!("{} -> {}", response.url(), response.status());
+        self.check_redirect(&response)?;
+        self.check_status(&response)?;
+
+        let header = content_type(response.headers());
+        let path = response.url().path().to_string();
+        let mut head = Vec::new();
+        let mut size = 0;
+        let mut too_large = false;
+        match response.content_length() {
+            Some(length) if check_size(self.config.max_size, length).is_err() => {
+                size = length;
+                too_large = true;
+            }
+            _ => {
+                let mut chunks = response.bytes_stream();
+                while let Some(chunk) = chunks.next().await.transpose()? {
+                    let wanted = sniff::SNIFF_LEN.saturating_sub(head.len()).min(chunk.len());
+                    head.extend_from_slice(&chunk[..wanted]);
+                    size += chunk.len() as u64;
+                    if check_size(self.config.max_size, size).is_err() {
+                        too_large = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let media_type = self.resolve_content_type(header.as_deref(), Some(&path), Some(&head));
+        let recognized = media_type.is_some();
+        let media_type = media_type.unwrap_or_else(|| self.config.default_media_type.clone());
+        Ok(ValidationReport {
+            allowed: self.check_allowed_type(&media_type).is_ok(),
+            recognized,
+            media_type,
+            size,
+            too_large,
+        })
+    }
+
+    /// 资源不超过 `threshold` 字节时内联为 DataUrl，否则返回 [`AssetRef::Linked`]
+    ///
+    /// 响应提供 Content-Length 时无需下载即可判断，否则最多读取 `threshold` 字节。
+    /// `max_size` 小于阈值时，超出 `max_size` 仍返回 [`DataUrlError::TooLarge`]
+    pub async fn fetch_or_link(
+        &self,
+        url: impl IntoUrl,
+        threshold: usize,
+    ) -> Result<AssetRef, DataUrlError> {
+        let url = url.into_url()?;
+        let request = self.get(&url)?;
+        let _permit = self.acquire_host(url.as_str()).await;
+        let (response, attempts) = self.send(request).await?;
+        debug!("{} -> {}", response.url(), response.status());
+        self.check_redirect(&response)?;
+        self.check_status(&response)?;
+
+        let meta = FetchMeta::new(&response, attempts);
+        let link = self
+            .config
+            .max_size
+            .is_none_or(|max_size| max_size >= threshold);
+        let mut reader = self.clone();
+        if link {
+            reader.config_mut().max_size = Some(threshold);
+        }
+        match reader.read_data_url(response, &mut |_, _| {}).await {
+            Ok(data_url) => Ok(AssetRef::Inlined(data_url)),
+            Err(DataUrlError::TooLarge { .. }) if link => Ok(AssetRef::Linked {
+                url: meta.final_url.clone(),
+                media_type: self
+                    .resolve_content_type(
+                        meta.content_type().as_deref(),
+                        Some(meta.final_url.path()),
+                        None,
+                    )
+                    .unwrap_or_else(|| self.config.default_media_type.clone()),
+                meta: Box::new(meta),
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 并发获取多个 URL，结果顺序与输入顺序一致
+    pub async fn fetch_many(&self, urls: &[&str]) -> Vec<Result<DataUrl, DataUrlError>> {
+        let budget = self.config.total_byte_budget.map(Budget::new);
+        let budget = budget.as_ref();
+        let mut results: Vec<_> = stream::iter(urls.iter().enumerate())
+            .map(|(index, url)| async move { (index, self.fetch_budgeted(url, budget).await) })
+            .buffer_unordered(self.config.concurrency)
+            .collect()
+            .await;
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// 并发获取多个 URL，按完成顺序逐个产出 `(url, 结果)`，并发数与 [`fetch_many`](Self::fetch_many) 相同
+    ///
+    /// 适合增量处理结果，例如逐个写入磁盘或更新进度条
+    pub fn fetch_stream<I, S>(
+        &self,
+        urls: I,
+    ) -> impl Stream<Item = (String, Result<DataUrl, DataUrlError>)> + '_
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let urls: Vec<String> = urls.into_iter().map(Into::into).collect();
+        let budget = self
+            .config
+            .total_byte_budget
+            .map(|limit| Arc::new(Budget::new(limit)));
+        stream::iter(urls)
+            .map(move |url| {
+                let budget = budget.clone();
+                async move {
+                    let result = self.fetch_budgeted(&url, budget.as_deref()).await;
+                    (url, result)
+                }
+            })
+            .buffer_unordered(self.config.concurrency)
+    }
+
+    /// 批量获取中的单个请求，预算已用尽时不发出请求
+    ///
+    /// 下载量不超过发出请求时的剩余预算；其他请求用尽预算后立即中止
+    async fn fetch_budgeted(
+        &self,
+        url: &str,
+        budget: Option<&Budget>,
+    ) -> Result<DataUrl, DataUrlError> {
+        let Some(budget) = budget else {
+            return self.fetch(url).await;
+        };
+        let remaining = budget.remaining().ok_or_else(|| budget.exceeded(url))?;
+        let mut fetcher = self.clone();
+        let capped = self
+            .config
+            .max_size
+            .is_none_or(|max_size| max_size > remaining);
+        if capped {
+            fetcher.config_mut().max_size = Some(remaining);
+        }
+
+        let result = budget
+            .exhausted
+            .run_until_cancelled(fetcher.fetch(url))
+            .await
+            .ok_or_else(|| budget.exceeded(url))?;
+        match result {
+            Ok(data_url) => {
+                budget.charge(url, data_url.data.len())?;
+                Ok(data_url)
+            }
+            // 超出的是剩余预算而不是 max_size
+            Err(DataUrlError::TooLarge { .. }) if capped => {
+                budget.exhaust();
+                Err(budget.exceeded(url))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 使用指定的 Accept 请求头获取资源，例如 `image/webp,image/png;q=0.8`
+    ///
+    /// 返回的媒体类型以服务器实际响应为准
+    pub async fn fetch_as(&self, url: impl IntoUrl, accept: &str) -> Result<DataUrl, DataUrlError> {
+        let url = url.into_url()?;
+        self.fetch_request(url.as_str(), self.get(&url)?.header(ACCEPT, accept))
+            .await
+    }
+
+    /// 获取资源，但忽略响应的 Content-Type，强制使用指定的媒体类型
+    ///
+    /// 适合服务器标错类型 (如把 PNG 标为 `application/octet-stream`) 而调用方已知真实类型的场景，
+    /// 编码策略与 `allowed_types` 均按指定的媒体类型检查；
+    /// 得到的类型与普通请求不同，因此不读写缓存
+    pub async fn fetch_as_type(
+        &self,
+        url: impl IntoUrl,
+        media_type: &str,
+    ) -> Result<DataUrl, DataUrlError> {
+        let mut fetcher = self
+            .clone()
+            .with_content_type_resolver(ContentTypeResolver::new([ContentTypeSource::Override(
+                media_type.to_string(),
+            )]));
+        fetcher.config_mut().cache = None;
+        fetcher.fetch(url).await
+    }
+
+    /// 获取资源，并要求媒体类型以 `expected_prefix` 开头 (不区分大小写)，如 `image/`
+    ///
+    /// 用于识别返回 200 但内容为 HTML 错误页的 CDN，类型不符时返回
+    /// [`DataUrlError::UnexpectedContentType`]
+    pub async fn fetch_expecting(
+        &self,
+        url: impl IntoUrl,
+        expected_prefix: &str,
+    ) -> Result<DataUrl, DataUrlError> {
+        let data_url = self.fetch(url).await?;
+        let matches = data_url
+            .media_type
+            .get(..expected_prefix.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(expected_prefix));
+        if !matches {
+            return Err(DataUrlError::UnexpectedContentType {
+                expected: expected_prefix.to_string(),
+                got: data_url.media_type,
+            });
+        }
+        Ok(data_url)
+    }
+
+    /// 附加自定义请求头 (如 User-Agent、Referer、Cookie) 获取资源并转换为 DataUrl
+    pub async fn fetch_with_headers(
+        &self,
+        url: impl IntoUrl,
+        headers: HeaderMap,
+    ) -> Result<DataUrl, DataUrlError> {
+        let url = url.into_url()?;
+        self.fetch_request(url.as_str(), self.get(&url)?.headers(headers))
+            .await
+    }
+
+    /// 使用内部共享的客户端创建任意方法的请求，可附加请求体后传给 [`fetch_with_request`]
+    ///
+    /// [`fetch_with_request`]: Self::fetch_with_request
+    pub fn request(
+        &self,
+        method: Method,
+        url: impl IntoUrl,
+    ) -> Result<RequestBuilder, DataUrlError> {
+        let url = url.into_url()?;
+        self.config.url_policy.check(&url)?;
+        Ok(self.client.request(method, url))
+    }
+
+    /// 发送调用方构造的请求 (如带 JSON 请求体的 POST) 并将响应转换为 DataUrl
+    ///
+    /// 同样遵循 URL 访问策略、重试、重定向与状态码检查及大小限制，但不使用缓存与请求合并；
+    /// 转换方式与 [`response_to_data_url`] 一致
+    ///
+    /// [`response_to_data_url`]: Self::response_to_data_url
+    pub async fn fetch_with_request(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<DataUrl, DataUrlError> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        self.config.url_policy.check(request.url())?;
+
+        let _permit = self.acquire_host(request.url().as_str()).await;
+        let (response, _) = self
+            .send(RequestBuilder::from_parts(client, request))
+            .await?;
+        debug!("{} -> {}", response.url(), response.status());
+        self.check_redirect(&response)?;
+        self.check_status(&response)?;
+        self.response_to_data_url(response).await
+    }
+
+    /// 按 URL 访问策略检查后创建 GET 请求
+    fn get(&self, url: &Url) -> Result<RequestBuilder, DataUrlError> {
+        self.config.url_policy.check(url)?;
+        Ok(self.client.get(url.clone()))
+    }
+
+    /// 发送请求并将响应转换为 DataUrl
+    async fn fetch_request(
+        &self,
+        url: &str,
+        request: RequestBuilder,
+    ) -> Result<DataUrl, DataUrlError> {
+        self.fetch_request_with_meta(url, request, &mut |_, _| {})
+            .await
+            .map(|(data_url, _)| data_url)
+    }
+
+    /// 发送请求并将响应转换为 DataUrl，同时返回响应元数据
+    async fn fetch_request_with_meta(
+        &self,
+        url: &str,
+        mut request: RequestBuilder,
+        progress: Progress<'_>,
+    ) -> Result<(DataUrl, FetchMeta), DataUrlError> {
+        let cached = self.config.cache.as_ref().and_then(|cache| cache.get(url));
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let _permit = self.acquire_host(url).await;
+        let (response, attempts) = self.send(request).await?;
+        debug!("{} -> {}", response.url(), response.status());
+        let meta = FetchMeta::new(&response, attempts);
+        // 304 也属于 3xx，需在检查重定向之前处理
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(entry) = cached
+        {
+            return Ok((entry.data_url, meta));
+        }
+        self.check_redirect(&response)?;
+        self.check_status(&response)?;
+        if let Some(length) = response.content_length() {
+            self.check_inline_threshold(url, length)?;
+        }
+
+        let cache = self
+            .config
+            .cache
+            .as_ref()
+            .filter(|_| response.status().is_success());
+        let etag = header_string(response.headers(), ETAG);
+        let last_modified = header_string(response.headers(), LAST_MODIFIED);
+        let data_url = self.read_data_url(response, progress).await?;
+        self.check_inline_threshold(url, data_url.data.len() as u64)?;
+
+        if let Some(cache) = cache
+            && (etag.is_some() || last_modified.is_some())
+        {
+            cache.put(
+                url,
+                CacheEntry {
+                    data_url: data_url.clone(),
+                    etag,
+                    last_modified,
+                },
+            );
+        }
+        Ok((data_url, meta))
+    }
+
+    /// 发送请求，按配置的重试策略处理暂时性错误
+    /// 返回最终响应与发送次数
+    async fn send(&self, mut request: RequestBuilder) -> Result<(Response, u32), DataUrlError> {
+        if let Some(timeout) = self.config.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(auth) = &self.config.auth {
+            request = auth.apply(request);
+        }
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            // 重试次数用尽或请求体无法复制时，直接返回本次结果
+            let retry = request
+                .try_clone()
+                .filter(|_| attempt <= self.config.retries);
+            let sent = self.send_once(request).await;
+            let Some(retry) = retry else {
+                return Ok((sent??, attempt));
+            };
+
+            let delay = match sent {
+                Err(DataUrlError::ReadTimeout) => {
+                    debug!("第 {} 次尝试等待响应头超时", attempt);
+                    backoff(self.config.retry_delay, attempt)
+                }
+                Err(e) => return Err(e),
+                Ok(Ok(response)) => {
+                    let status = response.status();
+                    let retry_after = matches!(
+                        status,
+                        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+                    )
+                    .then(|| retry_after(response.headers()))
+                    .flatten()
+                    .map(|delay| delay.min(self.config.max_retry_after));
+                    match retry_after {
+                        // 按服务器要求等待，同一主机的其他请求也一并推迟
+                        Some(delay) => {
+                            debug!("第 {} 次尝试返回 {}，{:?} 后重试", attempt, status, delay);
+                            if let Some(limiter) = &self.config.host_limiter {
+                                limiter.pause(response.url(), delay);
+                            }
+                            delay
+                        }
+                        None if is_retryable_status(status) => {
+                            debug!("第 {} 次尝试返回 {}", attempt, status);
+                            backoff(self.config.retry_delay, attempt)
+                        }
+                        None => {
+                            debug!("第 {} 次尝试完成", attempt);
+                            return Ok((response, attempt));
+                        }
+                    }
+                }
+                Ok(Err(e)) if is_transient(&e) => {
+                    debug!("第 {} 次尝试失败: {}", attempt, e);
+                    backoff(self.config.retry_delay, attempt)
+                }
+                Ok(Err(e)) => return Err(e.into()),
+            };
+
+            sleep(delay).await;
+            request = retry;
+        }
+    }
+
+    /// 发送一次请求，超过 `read_timeout` 仍未收到响应头时返回 [`DataUrlError::ReadTimeout`]
+    async fn send_once(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<reqwest::Result<Response>, DataUrlError> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let send = URL_POLICY.scope(self.config.url_policy.clone(), request.send());
+        #[cfg(target_arch = "wasm32")]
+        let send = request.send();
+        match self.config.read_timeout {
+            Some(duration) => timeout(duration, send)
+                .await
+                .ok_or(DataUrlError::ReadTimeout),
+            None => Ok(send.await),
+        }
+    }
+
+    /// 启用按主机限制时等待名额，返回的名额需持有到请求结束
+    async fn acquire_host(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let limiter = self.config.host_limiter.as_ref()?;
+        limiter.acquire(&Url::parse(url).ok()?).await
+    }
+
+    /// 修改配置，配置被其他副本共享时先复制一份
+    ///
+    /// 修改后的配置可能得到不同的结果，因此换用新的 [`SingleFlight`]，不再与其他副本合并请求
+    fn config_mut(&mut self) -> &mut Config {
+        let config = Arc::make_mut(&mut self.config);
+        if config.single_flight.is_some() {
+            config.single_flight = Some(Default::default());
+        }
+        config
+    }
+
+    /// 资源超出内联阈值时返回 [`DataUrlError::TooLargeToInline`]
+    fn check_inline_threshold(&self, url: &str, size: u64) -> Result<(), DataUrlError> {
+        match self.config.inline_threshold {
+            Some(threshold) if size > threshold as u64 => Err(DataUrlError::TooLargeToInline {
+                url: url.to_string(),
+                size,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// 设置了允许列表时，拒绝不在列表中的媒体类型
+    fn check_allowed_type(&self, media_type: &str) -> Result<(), DataUrlError> {
+        let Some(allowed) = &self.config.allowed_types else {
+            return Ok(());
+        };
+        let essence = essence(media_type);
+        let matches = allowed.iter().any(|pattern| {
+            if pattern.ends_with('/') {
+                essence
+                    .get(..pattern.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(pattern))
+            } else {
+                essence.eq_ignore_ascii_case(pattern)
+            }
+        });
+        if !matches {
+            return Err(DataUrlError::DisallowedType {
+                got: media_type.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 禁止重定向时，拒绝 3xx 响应
+    fn check_redirect(&self, response: &Response) -> Result<(), DataUrlError> {
+        if self.config.redirect == RedirectPolicy::None && response.status().is_redirection() {
+            return Err(DataUrlError::Redirect {
+                status: response.status().as_u16(),
+                location: header_string(response.headers(), LOCATION),
+            });
+        }
+        Ok(())
+    }
+
+    /// 未启用 `accept_status` 时，拒绝 4xx/5xx 响应
+    fn check_status(&self, response: &Response) -> Result<(), DataUrlError> {
+        check_status(self.config.accept_status, response.status())
+    }
+
+    /// 将 HTTP 响应转换为 DataUrl
+    pub async fn response_to_data_url(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<DataUrl, DataUrlError> {
+        self.read_data_url(response, &mut |_, _| {}).await
+    }
+
+    /// 读取响应并转换为 DataUrl，读取过程中报告进度
+    async fn read_data_url(
+        &self,
+        response: Response,
+        progress: Progress<'_>,
+    ) -> Result<DataUrl, DataUrlError> {
+        // 能在读取之前确定内容类型时先检查，不在允许列表中时无需读取响应体
+        let header = content_type(response.headers());
+        let path = response.url().path().to_string();
+        let content_type = self.resolve_content_type(header.as_deref(), Some(&path), None);
+        if let Some(content_type) = &content_type {
+            self.check_allowed_type(content_type)?;
+        }
+
+        // 读取响应字节
+        let bytes = read_body(response, self.config.max_size, progress).await?;
+
+        let content_type = match content_type {
+            Some(content_type) => content_type,
+            None => {
+                let content_type = self
+                    .resolve_content_type(header.as_deref(), Some(&path), Some(&bytes))
+                    .unwrap_or_else(|| self.config.default_media_type.clone());
+                self.check_allowed_type(&content_type)?;
+                content_type
+            }
+        };
+
+        self.build_data_url(content_type, bytes)
+    }
+
+    /// 读取本地文件并转换为 DataUrl，媒体类型根据扩展名推断
+    pub fn from_path(&self, path: impl AsRef<Path>) -> Result<DataUrl, DataUrlError> {
+        let path = path.as_ref();
+
+        check_size(self.config.max_size, std::fs::metadata(path)?.len())?;
+
+        let bytes = std::fs::read(path)?;
+        let content_type = match &self.config.content_type_resolver {
+            Some(resolver) => resolver.resolve(None, path.to_str(), Some(&bytes)),
+            None => mime_guess::from_path(path)
+                .first()
+                .map(|mime| mime.to_string())
+                .or_else(|| self.sniff(&bytes)),
+        }
+        .unwrap_or_else(|| self.config.default_media_type.clone());
+
+        self.build_data_url(content_type, bytes)
+    }
+
+    /// 读取任意 [`AsyncRead`] 直到结束并转换为 DataUrl，遵循大小限制与编码策略
+    pub async fn from_reader(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        media_type: &str,
+    ) -> Result<DataUrl, DataUrlError> {
+        let data = read_to_end(reader, self.config.max_size).await?;
+        self.build_data_url(media_type.to_string(), data)
+    }
+
+    /// 从任意来源创建 DataUrl，各来源同样遵循大小限制与编码策略
+    pub async fn build(&self, source: Source) -> Result<DataUrl, DataUrlError> {
+        match source {
+            Source::Url(url) => self.fetch(url).await,
+            Source::Path(path) => self.from_path(path),
+            Source::Bytes { data, media_type } => {
+                check_size(self.config.max_size, data.len() as u64)?;
+                self.build_data_url(media_type, data)
+            }
+            Source::Reader(reader) => {
+                let data = read_to_end(reader, self.config.max_size).await?;
+                // 没有其他可用的类型信息，不论是否开启嗅探都按内容推断
+                let media_type = self
+                    .resolve_content_type(None, None, Some(&data))
+                    .or_else(|| sniff::sniff(&data).map(str::to_string))
+                    .unwrap_or_else(|| self.config.default_media_type.clone());
+                self.build_data_url(media_type, data)
+            }
+        }
+    }
+
+    /// 按编码策略创建 DataUrl，并检查渲染后的长度限制
+    ///
+    /// SVG 开头的 UTF-8 BOM 会被去掉：它对 XML 没有意义，百分号编码后还可能导致浏览器无法渲染
+    fn build_data_url(
+        &self,
+        media_type: String,
+        mut data: Vec<u8>,
+    ) -> Result<DataUrl, DataUrlError> {
+        if essence(&media_type).eq_ignore_ascii_case("image/svg+xml") && data.starts_with(UTF8_BOM)
+        {
+            data.drain(..UTF8_BOM.len());
+        }
+        let base64_encoded = self.config.encoding.use_base64(&media_type);
+        let mut data_url = DataUrl::new(media_type, data, base64_encoded);
+        if self.config.strip_params {
+            data_url.media_type = essence(&data_url.media_type).to_string();
+        }
+
+        if let Some(limit) = self.config.max_encoded_len {
+            let actual = data_url.encoded_len();
+            if actual > limit {
+                return Err(DataUrlError::TooLong { limit, actual });
+            }
+        }
+        Ok(data_url)
+    }
+
+    /// 按判定顺序确定媒体类型，未设置时先用响应头，再在启用嗅探时根据数据推断
+    ///
+    /// `data` 为 `None` 表示尚未读取数据，见 [`ContentTypeResolver::resolve`]
+    fn resolve_content_type(
+        &self,
+        header: Option<&str>,
+        path: Option<&str>,
+        data: Option<&[u8]>,
+    ) -> Option<String> {
+        match &self.config.content_type_resolver {
+            Some(resolver) => resolver.resolve(header, path, data),
+            None => header.map(str::to_string).or_else(|| self.sniff(data?)),
+        }
+    }
+
+    /// 启用内容嗅探时，根据数据推断媒体类型
+    fn sniff(&self, data: &[u8]) -> Option<String> {
+        self.config
+            .sniffing
+            .then(|| sniff::sniff(data))
+            .flatten()
+            .map(str::to_string)
+    }
+}
+
+/// 读取响应体，并在读取过程中检查大小限制
+async fn read_body(
+    response: Response,
+    max_size: Option<usize>,
+    progress: Progress<'_>,
+) -> Result<Vec<u8>, DataUrlError> {
+    let total = response.content_length();
+
+    // 优先根据 Content-Length 提前拒绝
+    if let Some(length) = total {
+        check_size(max_size, length)?;
+    }
+
+    // 逐块读取，防止没有 Content-Length 的响应超出限制；
+    // 一旦超出立即返回并丢弃响应，不再下载剩余部分
+    let mut body = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await.transpose()? {
+        check_size(max_size, (body.len() + chunk.len()) as u64)?;
+        body.extend_from_slice(&chunk);
+        progress(body.len() as u64, total);
+    }
+    Ok(body)
+}
+
+/// 读取到结束为止，最多多读一个字节用于判断是否超出大小限制，超出时不再继续读取
+async fn read_to_end(
+    reader: impl AsyncRead + Unpin,
+    max_size: Option<usize>,
+) -> Result<Vec<u8>, DataUrlError> {
+    let mut data = Vec::new();
+    let limit = max_size.map_or(u64::MAX, |max_size| max_size as u64 + 1);
+    reader.take(limit).read_to_end(&mut data).await?;
+    check_size(max_size, data.len() as u64)?;
+    Ok(data)
+}
+
+/// 一次批量获取的总字节预算
+struct Budget {
+    limit: usize,
+    /// 剩余的字节数，超出预算后为 `None`
+    remaining: Mutex<Option<usize>>,
+    /// 超出预算时取消，用于中止其余正在进行的请求
+    exhausted: CancellationToken,
+}
+
+impl Budget {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            remaining: Mutex::new(Some(limit)),
+            exhausted: CancellationToken::new(),
+        }
+    }
+
+    /// 剩余的字节数，预算已超出时返回 `None`
+    fn remaining(&self) -> Option<usize> {
+        *self.remaining.lock().unwrap()
+    }
+
+    /// 扣除 `size` 字节，超出预算时返回错误，之后的检查也都失败
+    fn charge(&self, url: &str, size: usize) -> Result<(), DataUrlError> {
+        let mut remaining = self.remaining.lock().unwrap();
+        *remaining = remaining.and_then(|remaining| remaining.checked_sub(size));
+        match *remaining {
+            Some(_) => Ok(()),
+            None => {
+                self.exhausted.cancel();
+                Err(self.exceeded(url))
+            }
+        }
+    }
+
+    /// 标记预算已用尽并中止其余请求
+    fn exhaust(&self) {
+        *self.remaining.lock().unwrap() = None;
+        self.exhausted.cancel();
+    }
+
+    fn exceeded(&self, url: &str) -> DataUrlError {
+        DataUrlError::BudgetExceeded {
+            url: url.to_string(),
+            budget: self.limit,
+        }
+    }
+}
+
+/// 整理媒体类型允许列表，`image/*` 统一为 `image/`
+pub(crate) fn allowed_types(types: &[&str]) -> Vec<String> {
+    types
+        .iter()
+        .map(|pattern| {
+            let pattern = pattern.trim();
+            pattern.strip_suffix('*').unwrap_or(pattern).to_string()
+        })
+        .collect()
+}
+
+/// 下载进度回调，参数为已下载的字节数与总字节数
+type Progress<'a> = &'a mut (dyn FnMut(u64, Option<u64>) + Send);
+
+/// 是否为可重试的响应状态
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// 解析 `Retry-After` 响应头，支持秒数与 HTTP 日期两种形式，已经过去的日期视为无需等待
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = header_string(headers, RETRY_AFTER)?;
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    // wasm32-unknown-unknown 上无法获取当前时间，只支持秒数形式
+    if cfg!(target_arch = "wasm32") {
+        return None;
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(std::time::SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+/// 是否为值得重试的暂时性错误 (连接失败或请求未能发出)
+fn is_transient(e: &reqwest::Error) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    if e.is_connect() {
+        return true;
+    }
+    e.is_request()
+}
+
+/// 等待一段时间，wasm32 上没有 tokio 计时器，改用浏览器的 setTimeout
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::sleep;
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+/// 在 `duration` 内完成时返回结果，否则返回 `None`
+async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return tokio::time::timeout(duration, future).await.ok();
+
+    #[cfg(target_arch = "wasm32")]
+    match futures::future::select(std::pin::pin!(future), std::pin::pin!(sleep(duration))).await {
+        futures::future::Either::Left((output, _)) => Some(output),
+        futures::future::Either::Right(_) => None,
+    }
+}
+
+/// 计算第 `attempt` 次失败后的退避时间：`base * 2^(attempt - 1)`，再加上至多一半的随机抖动
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    let delay = base.saturating_mul(1 << (attempt - 1).min(16));
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    delay + delay.mul_f64((random % 1000) as f64 / 2000.0)
+}
+
+/// 读取字符串形式的请求头
+fn header_string(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 解析 Content-Type 响应头，缺失或无法解析时返回 `None`
+///
+/// 存在多个响应头时只使用第一个；整体无法解析时 (如 `text/html,text/html`)
+/// 再尝试解析第一个逗号之前的部分，仍然失败才返回 `None`。
+/// 类型、子类型与参数名统一转为小写，并去掉多余的空白；
+/// 二进制类型 (见 [`is_binary_mime`]) 的 charset 参数没有意义，会被去掉
+pub(crate) fn content_type(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(CONTENT_TYPE)?;
+    let Ok(value) = value.to_str() else {
+        debug!("Content-Type 不是有效的字符串: {:?}", value);
+        return None;
+    };
+
+    let parse = |value: &str| params::normalize_spacing(value).parse::<Mime>().ok();
+    let mime = parse(value).or_else(|| {
+        let first = value.split(',').next()?;
+        let mime = parse(first);
+        debug!("Content-Type 无法解析: {:?}，尝试使用 {:?}", value, first);
+        mime
+    });
+    if mime.is_none() {
+        debug!("Content-Type 无法解析: {:?}，将使用默认类型", value);
+    }
+    mime.map(|mime| {
+        let media_type = mime.to_string();
+        if is_binary_mime(&mime) && mime.get_param(mime::CHARSET).is_some() {
+            debug!("去掉二进制类型的 charset: {:?}", media_type);
+            params::remove_param(&media_type, "charset")
+        } else {
+            media_type
+        }
+    })
+}
+
+/// 是否为二进制类型：`image/*`、`audio/*`、`video/*`、`font/*` 与 `application/octet-stream`，
+/// 但 `image/svg+xml` 等 XML/JSON 文本类型除外
+fn is_binary_mime(mime: &Mime) -> bool {
+    let binary = [mime::IMAGE, mime::AUDIO, mime::VIDEO, mime::FONT].contains(&mime.type_())
+        || mime.essence_str() == mime::APPLICATION_OCTET_STREAM.essence_str();
+    binary && !is_text_mime(mime)
+}
+
+/// 检查响应状态码，`accept_status` 为 `false` 时拒绝 4xx/5xx
+pub(crate) fn check_status(accept_status: bool, status: StatusCode) -> Result<(), DataUrlError> {
+    if !accept_status && (status.is_client_error() || status.is_server_error()) {
+        return Err(DataUrlError::Status {
+            code: status.as_u16(),
+        });
+    }
+    Ok(())
+}
+
+/// 检查数据大小是否超出限制
+pub(crate) fn check_size(limit: Option<usize>, actual: u64) -> Result<(), DataUrlError> {
+    let actual = usize::try_from(actual).unwrap_or(usize::MAX);
+    match limit {
+        Some(limit) if actual > limit => Err(DataUrlError::TooLarge { limit, actual }),
+        _ => Ok(()),
+    }
+}
+
+/// 便捷函数：从 URL 获取资源并转换为 Data URL 字符串
+pub async fn url_to_data_url(url: &str) -> Result<String, DataUrlError> {
+    let converter = GetDataUrl::new();
+    let data_url = converter.fetch(url).await?;
+    Ok(data_url.to_string())
+}
+
+impl DataUrl {
+    /// 将已获得的 HTTP 响应转换为 base64 编码的 DataUrl，无需创建 [`GetDataUrl`]
+    ///
+    /// 使用默认配置：大小限制为 [`DEFAULT_MAX_SIZE`]，缺少 Content-Type 时为 [`DEFAULT_MEDIA_TYPE`]；
+    /// 需要自定义编码策略等配置时请使用 [`GetDataUrl::response_to_data_url`]
+    pub async fn from_response(response: Response) -> Result<Self, DataUrlError> {
+        let media_type =
+            content_type(response.headers()).unwrap_or_else(|| DEFAULT_MEDIA_TYPE.to_string());
+        let data = read_body(response, Some(DEFAULT_MAX_SIZE), &mut |_, _| {}).await?;
+        Ok(Self::new(media_type, data, true))
+    }
+
+    /// 读取任意 [`AsyncRead`] (如解压流) 直到结束，创建 base64 编码的 DataUrl
+    ///
+    /// 大小限制为 [`DEFAULT_MAX_SIZE`]，读取过程中超出时立即返回 [`DataUrlError::TooLarge`]；
+    /// 需要自定义配置时请使用 [`GetDataUrl::from_reader`]
+    pub async fn from_reader(
+        reader: impl AsyncRead + Unpin,
+        media_type: &str,
+    ) -> Result<Self, DataUrlError> {
+        let data = read_to_end(reader, Some(DEFAULT_MAX_SIZE)).await?;
+        Ok(Self::new(media_type, data, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryCache;
+    use reqwest::header::{REFERER, USER_AGENT};
+    use wiremock::matchers::{body_string, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_url_policy_blocks_localhost() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("secret"))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_url_policy(UrlPolicy::new().block_private_ips(true));
+        let url = format!("{}/latest/meta-data", mock_server.uri());
+        let result = converter.fetch(&url).await;
+        assert!(matches!(result, Err(DataUrlError::Blocked { .. })));
+
+        let url = url.replace("127.0.0.1", "localhost");
+        let result = converter.fetch(&url).await;
+        assert!(matches!(result, Err(DataUrlError::Blocked { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_url_policy_blocks_redirect() {
+        let mock_server = MockServer::start().await;
+
+        // 重定向到被拒绝的主机时不再发出请求
+        let target = format!(
+            "{}/secret",
+            mock_server.uri().replace("127.0.0.1", "localhost")
+        );
+        Mock::given(method("GET"))
+            .and(path("/public"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", target.as_str()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("secret"))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_url_policy(UrlPolicy::new().deny_hosts(["localhost"]))
+            .with_retries(2)
+            .with_retry_delay(Duration::from_millis(10));
+        let url = format!("{}/public", mock_server.uri());
+        let result = converter.fetch(&url).await;
+        assert!(matches!(result, Err(DataUrlError::Blocked { url }) if url == target));
+    }
+
+    #[tokio::test]
+    async fn test_url_policy_blocks_scheme() {
+        let result = GetDataUrl::new().fetch("ftp://example.com/a.png").await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::Blocked { url }) if url == "ftp://example.com/a.png"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shared_client() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = Arc::new(Client::new());
+        let converter = GetDataUrl::with_shared_client(Arc::clone(&client));
+
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+
+        // 共享的客户端仍可由调用方直接使用
+        let response = converter
+            .client()
+            .get(mock_server.uri())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "Hello, World!");
+        assert_eq!(Arc::strong_count(&client), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clone_shares_client_and_config() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_max_size(1024);
+        let clone = converter.clone();
+        assert!(std::ptr::eq(converter.client(), clone.client()));
+        assert!(Arc::ptr_eq(&converter.config, &clone.config));
+
+        let data_url = clone.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+
+        // 修改副本的配置只影响副本，客户端仍然共享
+        let clone = clone.with_max_size(4);
+        assert!(!Arc::ptr_eq(&converter.config, &clone.config));
+        assert!(std::ptr::eq(converter.client(), clone.client()));
+        assert!(matches!(
+            clone.fetch(&mock_server.uri()).await,
+            Err(DataUrlError::TooLarge { limit: 4, .. })
+        ));
+        assert!(converter.fetch(&mock_server.uri()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cache_not_modified() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(header("If-None-Match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_raw(vec![0x89, b'P', b'N', b'G'], "image/png"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_cache(Arc::new(MemoryCache::new()));
+        let first = converter.fetch(&mock_server.uri()).await.unwrap();
+        let second = converter.fetch(&mock_server.uri()).await.unwrap();
+
+        assert_eq!(first.media_type, "image/png");
+        assert_eq!(second, first);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_empty_body() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/empty"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("", "text/plain"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/no-content"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new();
+
+        let data_url = converter
+            .fetch(&format!("{}/empty", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(data_url.to_string(), "data:text/plain;base64,");
+
+        let data_url = converter
+            .fetch(&format!("{}/no-content", mock_server.uri()))
+            .await
+            .unwrap();
+        assert!(data_url.data.is_empty());
+        assert_eq!(data_url.media_type, "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_max_encoded_len() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        // "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ==" 共 43 个字符
+        let converter = GetDataUrl::new().with_max_encoded_len(40);
+        let result = converter.fetch(&mock_server.uri()).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLong {
+                limit: 40,
+                actual: 43
+            })
+        ));
+
+        let converter = GetDataUrl::new().with_max_encoded_len(43);
+        assert!(converter.fetch(&mock_server.uri()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_data_url() {
+        let mock_server = MockServer::start().await;
+
+        // 设置模拟响应
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new();
+        let result = converter.fetch(&mock_server.uri()).await;
+
+        assert!(result.is_ok());
+
+        let data_url = result.unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(data_url.base64_encoded);
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_url_to_data_url_convenience() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(r#"{"message": "Hello, World!"}"#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = url_to_data_url(&mock_server.uri()).await;
+        assert!(result.is_ok());
+
+        let data_url_str = result.unwrap();
+        println!("{}", data_url_str);
+        assert!(data_url_str.starts_with("data:application/json;base64,"));
+    }
+
+    #[tokio::test]
+    async fn test_max_size_exceeded() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 4096]))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_max_size(1024);
+        let result = converter.fetch(&mock_server.uri()).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge {
+                limit: 1024,
+                actual: 4096
+            })
+        ));
+
+        let converter = GetDataUrl::new().with_max_size(4096);
+        assert!(converter.fetch(&mock_server.uri()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_timeout(Duration::from_millis(200));
+        let start = std::time::Instant::now();
+        let result = converter.fetch(&mock_server.uri()).await;
+
+        assert!(matches!(result, Err(DataUrlError::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+        // 响应头迟迟不到
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_read_timeout(Duration::from_millis(200))
+            .with_timeout(Duration::from_secs(3));
+        let start = std::time::Instant::now();
+        let result = converter.fetch(&mock_server.uri()).await;
+        assert!(matches!(result, Err(DataUrlError::ReadTimeout)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        // 响应头立即返回，响应体下载缓慢
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    let _ = socket.read(&mut request).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nab")
+                        .await;
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    let _ = socket.write_all(b"cd").await;
+                });
+            }
+        });
+
+        let converter = GetDataUrl::new()
+            .with_read_timeout(Duration::from_millis(200))
+            .with_timeout(Duration::from_millis(600));
+        let result = converter.fetch(&format!("http://{}", addr)).await;
+        assert!(matches!(result, Err(DataUrlError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_read_timeout() {
+        let mock_server = MockServer::start().await;
+
+        // 第一次尝试迟迟不返回响应头，第二次正常返回
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_read_timeout(Duration::from_millis(200))
+            .with_retries(1)
+            .with_retry_delay(Duration::from_millis(10));
+        let start = std::time::Instant::now();
+        let (data_url, meta) = converter.fetch_with_meta(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"ok");
+        assert_eq!(meta.attempts, 2);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_auto_encoding() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_encoding(EncodingStrategy::Auto);
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+
+        assert!(!data_url.base64_encoded);
+        assert!(data_url.to_string().starts_with("data:text/plain,"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_strip_params() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("<svg/>", "image/svg+xml; charset=utf-8"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_strip_params(true);
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.media_type, "image/svg+xml");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_drops_binary_charset() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(b"\x89PNG\r\n\x1a\n".to_vec(), "image/png; charset=binary"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let data_url = GetDataUrl::new().fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+        assert_eq!(data_url.charset(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_svg_with_bom() {
+        let mock_server = MockServer::start().await;
+
+        let svg = b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+        let mut body = b"\xEF\xBB\xBF".to_vec();
+        body.extend_from_slice(svg);
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "image/svg+xml"))
+            .mount(&mock_server)
+            .await;
+
+        for encoding in [EncodingStrategy::Auto, EncodingStrategy::AlwaysBase64] {
+            let data_url = GetDataUrl::new()
+                .with_encoding(encoding)
+                .fetch(&mock_server.uri())
+                .await
+                .unwrap();
+            assert_eq!(data_url.data, svg);
+            let rendered = data_url.to_string();
+            assert!(!rendered.contains("%EF%BB%BF"), "{}", rendered);
+            assert_eq!(DataUrl::parse(&rendered).unwrap().data, svg);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_preserves_mime_params() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/html"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("<p>你好</p>", "text/html; charset=utf-8"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/multipart"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("", "multipart/mixed; boundary=ABC123"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new();
+
+        let data_url = converter
+            .fetch(&format!("{}/html", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "text/html; charset=utf-8");
+        assert_eq!(data_url.charset().as_deref(), Some("utf-8"));
+
+        let data_url = converter
+            .fetch(&format!("{}/multipart", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "multipart/mixed; boundary=ABC123");
+    }
+
+    #[test]
+    fn test_from_path() {
+        let path = std::env::temp_dir().join("get_data_url_test_from_path.png");
+        std::fs::write(&path, [0x89, b'P', b'N', b'G']).unwrap();
+
+        let data_url = GetDataUrl::new().from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(data_url.media_type, "image/png");
+        assert!(data_url.base64_encoded);
+        assert_eq!(data_url.data, [0x89, b'P', b'N', b'G']);
+    }
+
+    #[test]
+    fn test_from_path_missing() {
+        let result = GetDataUrl::new().from_path("/nonexistent/get_data_url.png");
+        assert!(
+            matches!(result, Err(DataUrlError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_sources() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("url", "text/plain"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_encoding(EncodingStrategy::AlwaysBase64)
+            .with_sniffing(true)
+            .with_max_size(8);
+
+        let data_url = converter
+            .build(Source::Url(format!("{}/a.txt", mock_server.uri())))
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, b"url");
+        assert!(data_url.base64_encoded);
+
+        let path = std::env::temp_dir().join("get_data_url_test_build.txt");
+        std::fs::write(&path, "path").unwrap();
+        let data_url = converter.build(Source::Path(path.clone())).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(data_url.base64_encoded);
+
+        let data_url = converter
+            .build(Source::Bytes {
+                data: b"<svg/>".to_vec(),
+                media_type: "image/svg+xml".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(data_url.to_string(), "data:image/svg+xml;base64,PHN2Zy8+");
+
+        let data_url = converter
+            .build(Source::Reader(Box::new(&b"GIF89a"[..])))
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "image/gif");
+        assert_eq!(data_url.data, b"GIF89a");
+
+        let result = converter
+            .build(Source::Bytes {
+                data: vec![0; 9],
+                media_type: "application/octet-stream".to_string(),
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge {
+                limit: 8,
+                actual: 9
+            })
+        ));
+
+        let result = converter
+            .build(Source::Reader(Box::new(&[0; 100][..])))
+            .await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge { limit: 8, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_build_reader_sniffs_by_default() {
+        let converter = GetDataUrl::new();
+        let data_url = converter
+            .build(Source::Reader(Box::new(&b"GIF89a"[..])))
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "image/gif");
+
+        let data_url = converter
+            .build(Source::Reader(Box::new(&b"plain"[..])))
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, DEFAULT_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_request() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chart"))
+            .and(body_string(r#"{"values":[1,2,3]}"#))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(&b"\x89PNG"[..], "image/png"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new();
+        let request = converter
+            .request(
+                Method::POST,
+                format!("{}/chart", mock_server.uri()).as_str(),
+            )
+            .unwrap()
+            .header(CONTENT_TYPE, "application/json")
+            .body(r#"{"values":[1,2,3]}"#);
+        let data_url = converter.fetch_with_request(request).await.unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+        assert_eq!(data_url.data, b"\x89PNG");
+
+        let request = Client::new().post(format!("{}/missing", mock_server.uri()));
+        let result = converter.fetch_with_request(request).await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 404 })));
+
+        let result = converter.request(Method::GET, "file:///etc/passwd");
+        assert!(matches!(result, Err(DataUrlError::Blocked { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_allowed_types() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/a.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(&b"\x89PNG"[..], "image/png"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html></html>", "text/html; charset=utf-8"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/unknown"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"GIF89a".to_vec()))
+            .mount(&mock_server)
+            .await;
+
+        for types in [&["image/*"][..], &["image/"], &["text/css", "IMAGE/PNG"]] {
+            let converter = GetDataUrl::new().with_allowed_types(types);
+            let data_url = converter
+                .fetch(&format!("{}/a.png", mock_server.uri()))
+                .await
+                .unwrap();
+            assert_eq!(data_url.media_type, "image/png");
+
+            let result = converter
+                .fetch(&format!("{}/page", mock_server.uri()))
+                .await;
+            assert!(matches!(
+                result,
+                Err(DataUrlError::DisallowedType { got }) if got == "text/html; charset=utf-8"
+            ));
+        }
+
+        // 缺少 Content-Type 时检查嗅探或默认的媒体类型
+        let converter = GetDataUrl::new().with_allowed_types(&["image/"]);
+        let url = format!("{}/unknown", mock_server.uri());
+        let result = converter.fetch(&url).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::DisallowedType { got }) if got == DEFAULT_MEDIA_TYPE
+        ));
+        let data_url = converter.with_sniffing(true).fetch(&url).await.unwrap();
+        assert_eq!(data_url.media_type, "image/gif");
+    }
+
+    #[tokio::test]
+    async fn test_content_type_resolver() {
+        use crate::ContentTypeSource::*;
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/logo.svg"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("<svg/>", "text/plain"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/download"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"GIF89a".to_vec()))
+            .mount(&mock_server)
+            .await;
+        let logo = format!("{}/logo.svg", mock_server.uri());
+        let download = format!("{}/download", mock_server.uri());
+
+        let data_url = GetDataUrl::new().fetch(&logo).await.unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+
+        let converter = GetDataUrl::new()
+            .with_content_type_resolver(ContentTypeResolver::new([Extension, Header, Sniff]));
+        let data_url = converter.fetch(&logo).await.unwrap();
+        assert_eq!(data_url.media_type, "image/svg+xml");
+        let data_url = converter.fetch(&download).await.unwrap();
+        assert_eq!(data_url.media_type, "image/gif");
+        let report = converter.validate(&logo).await.unwrap();
+        assert_eq!(report.media_type, "image/svg+xml");
+        let report = converter.validate(&download).await.unwrap();
+        assert_eq!(report.media_type, "image/gif");
+        match converter.fetch_or_link(&logo, 1).await.unwrap() {
+            AssetRef::Linked { media_type, .. } => assert_eq!(media_type, "image/svg+xml"),
+            AssetRef::Inlined(_) => panic!("应当返回链接"),
+        }
+
+        // 覆盖值在读取响应体之前即可确定，同样受允许列表约束
+        let converter = GetDataUrl::new()
+            .with_content_type_resolver(ContentTypeResolver::new([Override(
+                "image/png".to_string(),
+            )]))
+            .with_allowed_types(&["image/"]);
+        let data_url = converter.fetch(&logo).await.unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+
+        let converter = GetDataUrl::new()
+            .with_content_type_resolver(ContentTypeResolver::new([Header]))
+            .with_sniffing(true);
+        let data_url = converter.fetch(&download).await.unwrap();
+        assert_eq!(data_url.media_type, DEFAULT_MEDIA_TYPE);
+
+        let path = std::env::temp_dir().join("get_data_url_test_resolver.txt");
+        std::fs::write(&path, "GIF89a").unwrap();
+        let data_url = GetDataUrl::new()
+            .with_content_type_resolver(ContentTypeResolver::new([Sniff, Extension]))
+            .from_path(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data_url.media_type, "image/gif");
+    }
+
+    #[tokio::test]
+    async fn test_from_reader() {
+        let reader = std::io::Cursor::new(b"Hello, World!".to_vec());
+        let data_url = DataUrl::from_reader(reader, "text/plain").await.unwrap();
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ=="
+        );
+
+        let converter = GetDataUrl::new()
+            .with_encoding(EncodingStrategy::Auto)
+            .with_max_size(13);
+        let reader = std::io::Cursor::new(b"Hello, World!".to_vec());
+        let data_url = converter.from_reader(reader, "text/plain").await.unwrap();
+        assert_eq!(data_url.to_string(), "data:text/plain,Hello%2C%20World%21");
+
+        let reader = std::io::Cursor::new(vec![0; 1024]);
+        let result = converter
+            .from_reader(reader, "application/octet-stream")
+            .await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge {
+                limit: 13,
+                actual: 14
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_succeed() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_retries(3)
+            .with_retry_delay(Duration::from_millis(10));
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+
+        assert_eq!(data_url.data, b"Hello, World!");
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_too_many_requests_without_retry_after() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_retries(1)
+            .with_retry_delay(Duration::from_millis(10));
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+
+        assert_eq!(data_url.data, b"ok");
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_client_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_retries(3)
+            .with_retry_delay(Duration::from_millis(10));
+        let result = converter.fetch(&mock_server.uri()).await;
+
+        assert!(matches!(result, Err(DataUrlError::Status { code: 404 })));
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let base = Duration::from_millis(100);
+        for attempt in 1..=4 {
+            let delay = backoff(base, attempt);
+            let expected = base * 2u32.pow(attempt - 1);
+            assert!(delay >= expected && delay <= expected.mul_f64(1.5));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/new"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/old", mock_server.uri());
+
+        let data_url = GetDataUrl::new().fetch(&url).await.unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+
+        let converter = GetDataUrl::with_redirect_policy(RedirectPolicy::None);
+        let result = converter.fetch(&url).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::Redirect { status: 302, location: Some(location) }) if location == "/new"
+        ));
+
+        let converter = GetDataUrl::with_redirect_policy(RedirectPolicy::Limited(0));
+        assert!(matches!(
+            converter.fetch(&url).await,
+            Err(DataUrlError::TooManyRedirects { chain })
+                if chain == [url.clone(), format!("{}/new", mock_server.uri())]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_loop() {
+        let a = MockServer::start().await;
+        let b = MockServer::start().await;
+        let a_url = format!("{}/a.png", a.uri());
+        let b_url = format!("{}/b.png", b.uri());
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", b_url.as_str()))
+            .mount(&a)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", a_url.as_str()))
+            .mount(&b)
+            .await;
+
+        let result = GetDataUrl::new().fetch(&a_url).await;
+        assert!(matches!(
+            &result,
+            Err(DataUrlError::TooManyRedirects { chain })
+                if *chain == [a_url.clone(), b_url.clone(), a_url.clone()]
+        ));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains(&format!("{} -> {}", a_url, b_url))
+        );
+    }
+
+    #[test]
+    fn test_content_type_multiple_values() {
+        let mut headers = HeaderMap::new();
+        headers.append(CONTENT_TYPE, "image/png".parse().unwrap());
+        headers.append(CONTENT_TYPE, "text/html".parse().unwrap());
+        assert_eq!(content_type(&headers).as_deref(), Some("image/png"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "text/html,text/html".parse().unwrap());
+        assert_eq!(content_type(&headers).as_deref(), Some("text/html"));
+
+        // 引号中的逗号属于参数，整体可以解析时不截断
+        headers.insert(CONTENT_TYPE, "text/plain; x=\"a,b\"".parse().unwrap());
+        assert_eq!(
+            content_type(&headers).as_deref(),
+            Some("text/plain; x=\"a,b\"")
+        );
+
+        headers.insert(CONTENT_TYPE, "not a mime, text/html".parse().unwrap());
+        assert_eq!(content_type(&headers), None);
+    }
+
+    #[test]
+    fn test_content_type_normalization() {
+        let cases = [
+            ("IMAGE/PNG", "image/png"),
+            ("text/html ;charset=UTF-8", "text/html; charset=utf-8"),
+            (
+                "Text/Plain ;  Format = Flowed ; DelSp=yes",
+                "text/plain; format=Flowed; delsp=yes",
+            ),
+            ("TEXT/HTML , text/plain", "text/html"),
+            ("image/png; charset=binary", "image/png"),
+            (
+                "Audio/MPEG;Charset=UTF-8;rate=44100",
+                "audio/mpeg; rate=44100",
+            ),
+            (
+                "image/svg+xml; charset=utf-8",
+                "image/svg+xml; charset=utf-8",
+            ),
+        ];
+        for (value, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, value.parse().unwrap());
+            assert_eq!(content_type(&headers).as_deref(), Some(expected), "{value}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_parsed_url() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let url = Url::parse(&mock_server.uri())
+            .unwrap()
+            .join("a.txt")
+            .unwrap();
+        let data_url = GetDataUrl::new().fetch(&url).await.unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_single_flight() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("Hello, World!")
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_single_flight(true);
+        let uri = mock_server.uri();
+        let results = futures::future::join_all((0..8).map(|_| converter.fetch(&uri))).await;
+
+        for result in results {
+            assert_eq!(result.unwrap().data, b"Hello, World!");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_clones_with_different_config() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_bytes(vec![b'a'; 100])
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let a = GetDataUrl::new().with_single_flight(true);
+        let b = a.clone().with_max_size(10);
+        let uri = mock_server.uri();
+        let (a, b) = tokio::join!(a.fetch(&uri), b.fetch(&uri));
+        assert_eq!(a.unwrap().data.len(), 100);
+        assert!(matches!(b, Err(DataUrlError::TooLarge { .. })), "{:?}", b);
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        // 只有一个调用者时返回原始错误
+        let converter = GetDataUrl::new().with_single_flight(true);
+        let result = converter.fetch(mock_server.uri()).await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 404 })));
+    }
+
+    #[tokio::test]
+    async fn test_head() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("HEAD"))
+            .and(path("/image.png"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Type", "image/png")
+                    .insert_header("Content-Length", "4096"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/no-head"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/image.png", mock_server.uri());
+        let meta = GetDataUrl::new().head(&url).await.unwrap();
+        assert_eq!(meta.status, 200);
+        assert_eq!(meta.content_type().as_deref(), Some("image/png"));
+        assert_eq!(meta.content_length, Some(4096));
+
+        let result = GetDataUrl::new().with_max_size(1024).head(&url).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLarge {
+                limit: 1024,
+                actual: 4096
+            })
+        ));
+
+        let url = format!("{}/no-head", mock_server.uri());
+        let result = GetDataUrl::new().head(&url).await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 405 })));
+    }
+
+    #[tokio::test]
+    async fn test_validate() {
+        let mock_server = MockServer::start().await;
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+
+        Mock::given(method("GET"))
+            .and(path("/image"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png.clone()))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0u8; 4096], "image/png"))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/image", mock_server.uri());
+        let report = GetDataUrl::new().validate(&url).await.unwrap();
+        assert_eq!(report.media_type, DEFAULT_MEDIA_TYPE);
+        assert!(!report.recognized);
+        assert!(!report.is_valid());
+
+        let report = GetDataUrl::new()
+            .with_sniffing(true)
+            .validate(&url)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ValidationReport {
+                media_type: "image/png".to_string(),
+                recognized: true,
+                size: png.len() as u64,
+                too_large: false,
+                allowed: true,
+            }
+        );
+        assert!(report.is_valid());
+
+        let report = GetDataUrl::new()
+            .with_sniffing(true)
+            .with_allowed_types(&["text/"])
+            .validate(&url)
+            .await
+            .unwrap();
+        assert!(report.recognized);
+        assert!(!report.allowed);
+        assert!(!report.is_valid());
+        let report = GetDataUrl::new()
+            .with_sniffing(true)
+            .with_allowed_types(&["image/"])
+            .validate(&url)
+            .await
+            .unwrap();
+        assert!(report.allowed);
+        assert!(report.is_valid());
+
+        let url = format!("{}/large", mock_server.uri());
+        let report = GetDataUrl::new()
+            .with_max_size(1024)
+            .validate(&url)
+            .await
+            .unwrap();
+        assert!(report.too_large);
+        assert_eq!(report.size, 4096);
+        assert!(!report.is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_progress() {
+        let mock_server = MockServer::start().await;
+        let body = vec![7u8; 64 * 1024];
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+            .mount(&mock_server)
+            .await;
+
+        let mut calls = Vec::new();
+        let data_url = GetDataUrl::new()
+            .fetch_with_progress(mock_server.uri(), |downloaded, total| {
+                calls.push((downloaded, total))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(data_url.data, body);
+        assert!(!calls.is_empty());
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(
+            calls.last(),
+            Some(&(body.len() as u64, Some(body.len() as u64)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_meta() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/new"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-Request-Id", "42")
+                    .set_body_string("Hello, World!"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/old", mock_server.uri());
+        let (data_url, meta) = GetDataUrl::new().fetch_with_meta(&url).await.unwrap();
+
+        assert_eq!(data_url.data, b"Hello, World!");
+        assert_eq!(meta.final_url.path(), "/new");
+        assert!(meta.redirected(&url));
+        assert_eq!(meta.status, 200);
+        assert_eq!(meta.content_length, Some(13));
+        assert_eq!(meta.headers["x-request-id"], "42");
+        assert_eq!(meta.attempts, 1);
+        assert!(!meta.is_https());
+    }
+
+    /// 启动只接受一个连接的 HTTPS 服务器 (自签名证书)，对任何请求返回 `response`
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    async fn https_server(response: String) -> String {
+        use tokio::io::AsyncWriteExt as _;
+        use tokio_rustls::rustls::pki_types::pem::PemObject as _;
+        use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+        use tokio_rustls::rustls::{ServerConfig, crypto};
+
+        let certs = CertificateDer::pem_slice_iter(include_bytes!("../testdata/localhost.crt"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key =
+            PrivateKeyDer::from_pem_slice(include_bytes!("../testdata/localhost.key")).unwrap();
+        let config =
+            ServerConfig::builder_with_provider(Arc::new(crypto::aws_lc_rs::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0];
+                if stream.read(&mut byte).await.unwrap() == 0 {
+                    break;
+                }
+                request.push(byte[0]);
+            }
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        });
+        format!("https://localhost:{}", addr.port())
+    }
+
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    #[tokio::test]
+    async fn test_is_https_after_redirect() {
+        let mock_server = MockServer::start().await;
+        let converter = GetDataUrl::with_client(
+            Client::builder()
+                .redirect(RedirectPolicy::default().into())
+                .tls_danger_accept_invalid_certs(true)
+                .build()
+                .unwrap(),
+        );
+
+        // https 被重定向到 http：由最终 URL 决定，不算安全
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("plain"))
+            .mount(&mock_server)
+            .await;
+        let https = https_server(format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}/new\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            mock_server.uri()
+        ))
+        .await;
+        let url = format!("{}/old", https);
+        let (data_url, meta) = converter.fetch_with_meta(&url).await.unwrap();
+        assert_eq!(data_url.data, b"plain");
+        assert_eq!(
+            meta.final_url.as_str(),
+            format!("{}/new", mock_server.uri())
+        );
+        assert!(!meta.is_https());
+
+        // http 被重定向到 https
+        let https = https_server(
+            "HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\nsecure".to_string(),
+        )
+        .await;
+        Mock::given(method("GET"))
+            .and(path("/upgrade"))
+            .respond_with(
+                ResponseTemplate::new(302)
+                    .insert_header("Location", format!("{}/a", https).as_str()),
+            )
+            .mount(&mock_server)
+            .await;
+        let url = format!("{}/upgrade", mock_server.uri());
+        let (data_url, meta) = converter.fetch_with_meta(&url).await.unwrap();
+        assert_eq!(data_url.data, b"secure");
+        assert!(meta.redirected(&url));
+        assert!(meta.is_https());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_sniffing() {
+        let mock_server = MockServer::start().await;
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png))
+            .mount(&mock_server)
+            .await;
+
+        let data_url = GetDataUrl::new().fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.media_type, "application/octet-stream");
+
+        let converter = GetDataUrl::new().with_sniffing(true);
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_inline_threshold() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/small"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![1u8; 16]))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![1u8; 4096]))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_inline_threshold(1024);
+        let small = format!("{}/small", mock_server.uri());
+        assert_eq!(converter.fetch(&small).await.unwrap().data.len(), 16);
+
+        let large = format!("{}/large", mock_server.uri());
+        let result = converter.fetch(&large).await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::TooLargeToInline { url, size: 4096 }) if url == large
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_as_type() {
+        let mock_server = MockServer::start().await;
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(png.clone(), "text/plain"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_encoding(EncodingStrategy::Auto);
+        let data_url = converter.fetch(mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(!data_url.base64_encoded);
+
+        let data_url = converter
+            .fetch_as_type(mock_server.uri(), "image/png")
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+        assert!(data_url.base64_encoded);
+        assert_eq!(data_url.data, png);
+
+        // 允许列表检查的是指定的类型，而不是服务器返回的类型
+        let converter = GetDataUrl::new().with_allowed_types(&["image/"]);
+        let data_url = converter
+            .fetch_as_type(mock_server.uri(), "image/png")
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+        let result = converter
+            .fetch_as_type(mock_server.uri(), "text/html")
+            .await;
+        assert!(matches!(result, Err(DataUrlError::DisallowedType { .. })));
+        assert!(matches!(
+            converter.fetch(mock_server.uri()).await,
+            Err(DataUrlError::DisallowedType { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_expecting() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/logo.png"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(b"<html>Not Found</html>".to_vec(), "text/html"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/logo.png", mock_server.uri());
+        let result = GetDataUrl::new().fetch_expecting(&url, "image/").await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::UnexpectedContentType { expected, got })
+                if expected == "image/" && got == "text/html"
+        ));
+
+        let data_url = GetDataUrl::new()
+            .fetch_expecting(&url, "TEXT/")
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "text/html");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_or_link() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/small.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0u8; 16], "image/png"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/old.png"))
+            .respond_with(ResponseTemplate::new(301).insert_header("Location", "/large.png"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0u8; 4096], "image/png"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new();
+        let small = format!("{}/small.png", mock_server.uri());
+        let result = converter.fetch_or_link(&small, 1024).await.unwrap();
+        assert!(matches!(result, AssetRef::Inlined(data_url) if data_url.len() == 16));
+
+        let old = format!("{}/old.png", mock_server.uri());
+        match converter.fetch_or_link(&old, 1024).await.unwrap() {
+            AssetRef::Linked {
+                url,
+                media_type,
+                meta,
+            } => {
+                assert_eq!(url.path(), "/large.png");
+                assert_eq!(media_type, "image/png");
+                assert_eq!(meta.content_length, Some(4096));
+            }
+            AssetRef::Inlined(_) => panic!("应返回链接"),
+        }
+
+        // max_size 小于阈值时仍然报错
+        let result = converter
+            .clone()
+            .with_max_size(1024)
+            .fetch_or_link(&old, 8192)
+            .await;
+        assert!(matches!(result, Err(DataUrlError::TooLarge { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cancellable() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("slow")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let start = std::time::Instant::now();
+        let result = GetDataUrl::new()
+            .fetch_cancellable(&mock_server.uri(), token)
+            .await;
+        assert!(matches!(result, Err(DataUrlError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_default_media_type() {
+        let mock_server = MockServer::start().await;
+
+        // 原始字节响应没有 Content-Type
+        Mock::given(method("GET"))
+            .and(path("/raw"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"raw".to_vec()))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/raw", mock_server.uri());
+        let data_url = GetDataUrl::new().fetch(&url).await.unwrap();
+        assert_eq!(data_url.media_type, DEFAULT_MEDIA_TYPE);
+
+        let converter = GetDataUrl::new().with_default_media_type("text/plain");
+        let data_url = converter.fetch(&url).await.unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_headers() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(header("User-Agent", "get-data-url-test"))
+            .and(header("Referer", "https://example.com/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, "get-data-url-test".parse().unwrap());
+        headers.insert(REFERER, "https://example.com/".parse().unwrap());
+
+        let data_url = GetDataUrl::new()
+            .fetch_with_headers(&mock_server.uri(), headers)
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_stream() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("slow")
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/fast"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fast"))
+            .mount(&mock_server)
+            .await;
+
+        let slow = format!("{}/slow", mock_server.uri());
+        let fast = format!("{}/fast", mock_server.uri());
+        let converter = GetDataUrl::new();
+        let results: Vec<_> = converter
+            .fetch_stream([slow.clone(), fast.clone()])
+            .collect()
+            .await;
+
+        // 按完成顺序产出，并保留对应的 URL
+        let urls: Vec<_> = results.iter().map(|(url, _)| url.clone()).collect();
+        assert_eq!(urls, vec![fast, slow]);
+        assert_eq!(results[0].1.as_ref().unwrap().data, b"fast");
+        assert_eq!(results[1].1.as_ref().unwrap().data, b"slow");
+    }
+
+    #[tokio::test]
+    async fn test_total_byte_budget() {
+        let mock_server = MockServer::start().await;
+        for index in 0..4 {
+            Mock::given(method("GET"))
+                .and(path(format!("/{}", index)))
+                .respond_with(ResponseTemplate::new(200).set_body_string("0123456789"))
+                .expect(match index {
+                    0 | 1 => 2,
+                    2 => 1,
+                    _ => 0,
+                })
+                .mount(&mock_server)
+                .await;
+        }
+
+        let urls: Vec<String> = (0..4)
+            .map(|index| format!("{}/{}", mock_server.uri(), index))
+            .collect();
+        let converter = GetDataUrl::new()
+            .with_concurrency(1)
+            .with_total_byte_budget(25);
+
+        let refs: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let results = converter.fetch_many(&refs).await;
+        assert!(results[0].is_ok() && results[1].is_ok());
+        // 第三个超出预算，第四个不再发出请求
+        for (result, url) in results[2..].iter().zip(&urls[2..]) {
+            assert!(matches!(
+                result,
+                Err(DataUrlError::BudgetExceeded { url: skipped, budget: 25 }) if skipped == url
+            ));
+        }
+
+        // 每次批量获取的预算相互独立
+        let results: Vec<_> = converter.fetch_stream(urls[..2].to_vec()).collect().await;
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_total_byte_budget_in_flight() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("slow")
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/large"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![0u8; 1024]))
+            .mount(&mock_server)
+            .await;
+
+        // 单个资源超出剩余预算时不再下载，正在进行的请求随之中止
+        let slow = format!("{}/slow", mock_server.uri());
+        let large = format!("{}/large", mock_server.uri());
+        let converter = GetDataUrl::new().with_total_byte_budget(100);
+        let start = std::time::Instant::now();
+        let results = converter.fetch_many(&[&slow, &large]).await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+        for (result, url) in results.iter().zip([&slow, &large]) {
+            assert!(matches!(
+                result,
+                Err(DataUrlError::BudgetExceeded { url: skipped, budget: 100 }) if skipped == url
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many() {
+        let mock_server = MockServer::start().await;
+
+        // 越靠前的请求响应越慢，验证结果仍按输入顺序返回
+        for (index, delay) in [(0, 300), (1, 150), (2, 0)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/{}", index)))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_string(index.to_string())
+                        .set_delay(Duration::from_millis(delay)),
+                )
+                .mount(&mock_server)
+                .await;
+        }
+
+        let urls: Vec<String> = (0..3)
+            .map(|index| format!("{}/{}", mock_server.uri(), index))
+            .collect();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+
+        let results = GetDataUrl::new()
+            .with_concurrency(2)
+            .fetch_many(&urls)
+            .await;
+
+        assert_eq!(results.len(), 3);
+        for (index, result) in results.iter().enumerate() {
+            assert_eq!(result.as_ref().unwrap().data, index.to_string().as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_host_limit_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+        // 统计同时处理中的请求数的最大值
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (active_server, peak_server) = (Arc::clone(&active), Arc::clone(&peak));
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (active, peak) = (Arc::clone(&active_server), Arc::clone(&peak_server));
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    while matches!(socket.read(&mut request).await, Ok(n) if n > 0) {
+                        let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(current, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let urls: Vec<String> = (0..6).map(|i| format!("http://{}/{}", addr, i)).collect();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let results = GetDataUrl::new()
+            .with_concurrency(6)
+            .with_host_limit(HostLimit::new().max_concurrent(2))
+            .fetch_many(&urls)
+            .await;
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_too_many_requests() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new()
+            .with_retries(1)
+            .with_host_limit(HostLimit::new());
+        let start = std::time::Instant::now();
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"ok");
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        // 没有重试次数时 429 直接作为错误返回
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        let result = GetDataUrl::new().fetch(&mock_server.uri()).await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 429 })));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_service_unavailable() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "3600"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&mock_server)
+            .await;
+
+        // 等待时间不超过设置的上限
+        let converter = GetDataUrl::new()
+            .with_retries(1)
+            .with_max_retry_after(Duration::from_millis(200));
+        let start = std::time::Instant::now();
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"ok");
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        let date = std::time::SystemTime::now() + Duration::from_secs(30);
+        headers.insert(RETRY_AFTER, httpdate::fmt_http_date(date).parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(
+            delay > Duration::from_secs(28) && delay <= Duration::from_secs(30),
+            "{:?}",
+            delay
+        );
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, " 5 ".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("<h1>Oops</h1>"))
+            .mount(&mock_server)
+            .await;
+
+        let result = GetDataUrl::new().fetch(&mock_server.uri()).await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 500 })));
+
+        let converter = GetDataUrl::new().with_accept_status(true);
+        let data_url = converter.fetch(&mock_server.uri()).await.unwrap();
+        assert_eq!(data_url.data, b"<h1>Oops</h1>");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gzip_encoded() {
+        use std::io::Write;
+
+        let original = "Hello, World! ".repeat(100);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(original.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .set_body_raw(compressed.clone(), "text/plain"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let (data_url, meta) = GetDataUrl::new()
+            .fetch_with_meta(&mock_server.uri())
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, original.as_bytes());
+        assert_eq!(meta.content_encoding, None);
+
+        // 关闭自动解压时保留原始字节与 Content-Encoding
+        let (data_url, meta) = GetDataUrl::with_auto_decompress(false)
+            .fetch_with_meta(&mock_server.uri())
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, compressed);
+        assert_eq!(meta.content_encoding.as_deref(), Some("gzip"));
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert!(requests[0].headers.contains_key("accept-encoding"));
+        assert!(!requests[1].headers.contains_key("accept-encoding"));
+    }
+
+    #[tokio::test]
+    async fn test_max_size_streamed() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // 没有 Content-Length、无限发送分块数据的服务器
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+            if socket.write_all(head.as_bytes()).await.is_err() {
+                return;
+            }
+            let chunk = format!("400\r\n{}\r\n", "a".repeat(1024));
+            while socket.write_all(chunk.as_bytes()).await.is_ok() {}
+        });
+
+        let converter = GetDataUrl::new().with_max_size(4096);
+        let result = converter.fetch(&format!("http://{}", addr)).await;
+
+        match result {
+            Err(DataUrlError::TooLarge { limit, actual }) => {
+                assert_eq!(limit, 4096);
+                assert!(actual > 4096 && actual <= 4096 + 1024);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_as() {
+        let mock_server = MockServer::start().await;
+
+        // 服务器不支持 WebP，回退为 PNG
+        Mock::given(method("GET"))
+            .and(header("Accept", "image/webp"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("png", "image/png"))
+            .mount(&mock_server)
+            .await;
+
+        let data_url = GetDataUrl::new()
+            .fetch_as(&mock_server.uri(), "image/webp")
+            .await
+            .unwrap();
+        assert_eq!(data_url.media_type, "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_from_response() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let response = Client::new().get(mock_server.uri()).send().await.unwrap();
+        let data_url = DataUrl::from_response(response).await.unwrap();
+
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ=="
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/basic"))
+            .and(header("Authorization", "Basic dXNlcjpwYXNz"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("basic"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bearer"))
+            .and(header("Authorization", "Bearer secret-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("bearer"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let converter = GetDataUrl::new().with_basic_auth("user", Some("pass"));
+        let data_url = converter
+            .fetch(&format!("{}/basic", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, b"basic");
+        assert!(!format!("{:?}", converter).contains("pass\""));
+
+        let converter = GetDataUrl::new().with_bearer_token("secret-token");
+        let data_url = converter
+            .fetch(&format!("{}/bearer", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(data_url.data, b"bearer");
+        assert!(!format!("{:?}", converter).contains("secret-token"));
+
+        let result = GetDataUrl::new()
+            .fetch(&format!("{}/bearer", mock_server.uri()))
+            .await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 401 })));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_url() {
+        let converter = GetDataUrl::new();
+        let result = converter.fetch("not_a_valid_url").await;
+        assert!(matches!(
+            result,
+            Err(DataUrlError::InvalidUrl { url, source: url::ParseError::RelativeUrlWithoutBase })
+                if url == "not_a_valid_url"
+        ));
+
+        let result = converter.fetch("http://[::1").await;
+        assert!(matches!(result, Err(DataUrlError::InvalidUrl { .. })));
+    }
+}
@@ -0,0 +1,1519 @@
+//! Data URL 的构造、渲染、解析与解码，不依赖 async 运行时与 HTTP，关闭默认 feature 时同样可用
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use base64::alphabet;
+use base64::engine::general_purpose::{
+    GeneralPurpose, PAD, STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD,
+};
+use base64::engine::{DecodePaddingMode, Engine as _};
+use mime::Mime;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, percent_encode};
+
+#[cfg(feature = "image-meta")]
+use crate::image_meta;
+use crate::{DataUrlError, ParseError, Render, params, sniff};
+
+/// 解析时使用的宽松 base64 引擎，填充可有可无
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    PAD.with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// 无法确定媒体类型时使用的默认值
+pub const DEFAULT_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// RFC 2397 规定省略媒体类型时的默认值
+pub const RFC_DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// 非 base64 编码时需要转义的字符
+///
+/// RFC 3986 的非保留字符 (字母、数字及 `-` `.` `_` `~`) 保持原样，
+/// 其余字节 (包括 `%`、`#` 与空格) 均被转义
+pub(crate) const DATA_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Data URL 结构体，表示一个符合 RFC 2397 标准的数据 URL
+///
+/// # 不变式
+///
+/// `data` **始终**保存解码后的原始字节，无论它来自 [`GetDataUrl::fetch`](crate::GetDataUrl::fetch)、
+/// [`DataUrl::parse`] 还是 [`DataUrl::from_response`]；`base64_encoded` 只决定渲染时
+/// 使用 base64 还是百分号编码，不影响 `data` 的内容。因此对参数已是规范顺序的 DataUrl 都有
+/// `DataUrl::parse(&x.to_string()) == Ok(x)`
+///
+/// # 参数顺序
+///
+/// 渲染时参数按规范顺序输出：`charset` 在前，其余按名称字母顺序 (不区分大小写)，
+/// 便于快照测试与按内容寻址的缓存；`media_type` 字段本身保持原样，已是规范顺序时原样输出
+///
+/// 实现了 [`Hash`] 与 [`Ord`]，可用作集合的键或排序；比较与哈希会遍历整个 `data`，
+/// 开销与数据大小成正比。排序依次比较 `media_type`、`base64_encoded`、`data` 与 `fragment`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DataUrl {
+    /// 媒体类型 (MIME type)
+    pub media_type: String,
+    /// 是否是 base64 编码
+    pub base64_encoded: bool,
+    /// 数据内容，始终为解码后的原始字节，编码仅在渲染时进行
+    pub data: Vec<u8>,
+    /// `#` 之后的片段标识符 (不含 `#`)，渲染时原样追加到末尾
+    pub fragment: Option<String>,
+}
+
+impl DataUrl {
+    /// 创建一个新的 DataUrl
+    ///
+    /// 不会校验媒体类型，传入 `imagepng` 这类无效值时生成的 Data URL 无法被浏览器识别，
+    /// 需要校验时请使用 [`DataUrl::try_new`]
+    pub fn new(media_type: impl Into<String>, data: Vec<u8>, base64_encoded: bool) -> Self {
+        Self {
+            media_type: media_type.into(),
+            base64_encoded,
+            data,
+            fragment: None,
+        }
+    }
+
+    /// 创建一个新的 DataUrl，并校验媒体类型
+    ///
+    /// 媒体类型必须是有效的 `type/subtype`，类型与子类型会被转换为小写
+    pub fn try_new(
+        media_type: impl Into<String>,
+        data: Vec<u8>,
+        base64_encoded: bool,
+    ) -> Result<Self, ParseError> {
+        let media_type = media_type.into();
+        let mime = media_type
+            .parse::<Mime>()
+            .map_err(|_| ParseError::InvalidMediaType(media_type))?;
+        Ok(Self::new(mime.to_string(), data, base64_encoded))
+    }
+
+    /// 从内存中的字节创建 DataUrl，与 [`DataUrl::new`] 等价
+    pub fn from_bytes(data: Vec<u8>, media_type: impl Into<String>, base64_encoded: bool) -> Self {
+        Self::new(media_type, data, base64_encoded)
+    }
+
+    /// 从内存中的字节创建 base64 编码的 DataUrl，媒体类型根据数据开头的魔数推断
+    ///
+    /// 无法识别时为 [`DEFAULT_MEDIA_TYPE`]
+    pub fn from_bytes_sniffed(data: Vec<u8>) -> Self {
+        let media_type = sniff::sniff(&data).unwrap_or(DEFAULT_MEDIA_TYPE);
+        Self::new(media_type, data, true)
+    }
+
+    /// 从文本创建百分号编码的 DataUrl，`charset` 会作为媒体类型参数附加
+    ///
+    /// 适合在 CSS 中内联 SVG：相比 base64 更短且可读，`<`、`>`、`#`、`"` 等字符均会被转义
+    pub fn from_text(text: &str, media_type: &str, charset: Option<&str>) -> Self {
+        let media_type = match charset {
+            Some(charset) => format!("{};charset={}", media_type, charset),
+            None => media_type.to_string(),
+        };
+        Self::new(media_type, text.as_bytes().to_vec(), false)
+    }
+
+    /// 解析 Data URL 字符串，例如 `data:text/plain;base64,SGVsbG8=`
+    ///
+    /// 非 base64 的数据部分会进行百分号解码，解析后的 `data` 始终为原始字节；
+    /// 省略的媒体类型会展开为 [`RFC_DEFAULT_MEDIA_TYPE`]。
+    /// 与其他 URL 一致，第一个 `#` 之后为片段标识符，不属于数据，可通过 [`DataUrl::fragment`] 获取
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_base64_with(input, decode_base64)
+    }
+
+    /// 按指定的 base64 变体严格解析：只接受该变体的字母表，填充必须与变体一致
+    ///
+    /// 空白 (含百分号编码的换行) 仍会被忽略；非 base64 的 Data URL 与 [`DataUrl::parse`] 相同
+    pub fn parse_with(input: &str, variant: Base64Variant) -> Result<Self, ParseError> {
+        Self::parse_base64_with(input, |payload| {
+            let normalized: Vec<u8> = percent_decode_str(payload)
+                .filter(|byte| !byte.is_ascii_whitespace())
+                .collect();
+            variant
+                .engine()
+                .decode(normalized)
+                .map_err(ParseError::InvalidBase64)
+        })
+    }
+
+    fn parse_base64_with(
+        input: &str,
+        decode: impl FnOnce(&str) -> Result<Vec<u8>, ParseError>,
+    ) -> Result<Self, ParseError> {
+        let RawDataUrl {
+            media_type,
+            base64_encoded,
+            payload,
+            fragment,
+        } = RawDataUrl::split(input)?;
+
+        let data = if base64_encoded {
+            decode(payload)?
+        } else {
+            percent_decode_str(payload).collect()
+        };
+
+        // 按 RFC 2397，省略类型时默认为 text/plain，完全省略时还默认 charset=US-ASCII
+        let media_type = if media_type.is_empty() {
+            RFC_DEFAULT_MEDIA_TYPE.to_string()
+        } else if media_type.starts_with(';') {
+            format!("text/plain{}", params::decode_values(media_type))
+        } else {
+            params::decode_values(media_type).into_owned()
+        };
+
+        Ok(Self {
+            fragment: fragment.map(str::to_string),
+            ..Self::new(media_type, data, base64_encoded)
+        })
+    }
+
+    /// 片段标识符 (不含 `#`)，如 `data:image/svg+xml,...#icon` 中的 `icon`
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// 将媒体类型解析为 [`Mime`]，无法解析时返回 `None`
+    pub fn mime(&self) -> Option<Mime> {
+        self.media_type.parse().ok()
+    }
+
+    /// 是否为 `image/*` 类型
+    pub fn is_image(&self) -> bool {
+        self.mime().is_some_and(|mime| mime.type_() == mime::IMAGE)
+    }
+
+    /// 是否为 `text/*` 类型
+    pub fn is_text(&self) -> bool {
+        self.mime().is_some_and(|mime| mime.type_() == mime::TEXT)
+    }
+
+    /// 是否为 `application/*` 类型
+    pub fn is_application(&self) -> bool {
+        self.mime()
+            .is_some_and(|mime| mime.type_() == mime::APPLICATION)
+    }
+
+    /// 只解析 PNG、GIF、JPEG 的文件头获取图片宽高，需要启用 `image-meta` feature
+    ///
+    /// 非 `image/*` 类型或无法识别的格式返回 `None`
+    #[cfg(feature = "image-meta")]
+    pub fn image_dimensions(&self) -> Option<(u32, u32)> {
+        if !self.is_image() {
+            return None;
+        }
+        image_meta::dimensions(&self.data)
+    }
+
+    /// 获取媒体类型中的 charset 参数
+    pub fn charset(&self) -> Option<String> {
+        let mime = self.mime()?;
+        mime.get_param(mime::CHARSET)
+            .map(|charset| charset.to_string())
+    }
+
+    /// 按出现顺序返回媒体类型中的所有参数，带引号的值会去掉两侧引号
+    ///
+    /// 参数仍按原有顺序保存在 `media_type` 中，但渲染时按规范顺序输出：`charset` 在前，其余按名称字母顺序
+    pub fn params(&self) -> Vec<(&str, &str)> {
+        params::param_spans(&self.media_type)
+            .into_iter()
+            .map(|span| {
+                (
+                    &self.media_type[span.name],
+                    params::unquote(&self.media_type[span.value]),
+                )
+            })
+            .collect()
+    }
+
+    /// 获取指定参数的值，参数名不区分大小写，如 `param("filename")`
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params()
+            .into_iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// 设置参数的值：已存在时原位替换，否则追加到末尾；值包含特殊字符时自动加引号
+    ///
+    /// 渲染时值中的 `%`、`,`、`#` 与空白以百分号编码，解析后得到原值
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        let value = params::quote(value);
+        let existing = params::param_spans(&self.media_type)
+            .into_iter()
+            .find(|span| self.media_type[span.name.clone()].eq_ignore_ascii_case(name));
+        match existing {
+            Some(span) => self.media_type.replace_range(span.value, &value),
+            None => {
+                self.media_type.push(';');
+                self.media_type.push_str(name);
+                self.media_type.push('=');
+                self.media_type.push_str(&value);
+            }
+        }
+    }
+
+    /// 使用自定义选项渲染 Data URL，默认选项与 [`Display`] 输出一致
+    pub fn render(&self) -> Render<'_> {
+        Render::new(self)
+    }
+
+    /// 返回去掉所有参数、只保留 `type/subtype` 的副本，原值保持不变
+    pub fn without_params(&self) -> DataUrl {
+        Self {
+            media_type: essence(&self.media_type).to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// 替换数据，媒体类型、编码方式与片段保持不变，适合处理 (如压缩图片) 后重新渲染
+    pub fn with_data(mut self, data: Vec<u8>) -> DataUrl {
+        self.set_data(data);
+        self
+    }
+
+    /// 原位替换数据，媒体类型、编码方式与片段保持不变
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data;
+    }
+
+    /// 返回以 base64 渲染的副本，媒体类型与参数保持不变
+    pub fn to_base64(&self) -> DataUrl {
+        Self {
+            base64_encoded: true,
+            ..self.clone()
+        }
+    }
+
+    /// 返回以百分号编码渲染的副本，媒体类型与参数保持不变
+    pub fn to_percent(&self) -> DataUrl {
+        Self {
+            base64_encoded: false,
+            ..self.clone()
+        }
+    }
+
+    /// 规范形式的字符串，适合作为缓存与去重的键
+    ///
+    /// 总是使用带填充的标准 base64；媒体类型、参数名与 `charset` 的值转为小写，参数按规范顺序排列，
+    /// 值只在必要时加引号；默认媒体类型 `text/plain;charset=US-ASCII` 被省略，片段不包含在内。
+    /// 解码后的数据与媒体类型相同的两个 DataUrl 总是得到相同的字符串
+    pub fn canonical_string(&self) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.canonical_media_type(),
+            STANDARD.encode(&self.data)
+        )
+    }
+
+    /// 解码后的数据与规范化媒体类型的 SHA-256 (小写十六进制)，需要启用 `hash` feature
+    ///
+    /// 与编码方式和片段无关，媒体类型按 [`canonical_string`](Self::canonical_string) 的规则规范化；
+    /// 适合用作清单中的内容标识或缓存文件名
+    #[cfg(feature = "hash")]
+    pub fn content_hash(&self) -> String {
+        use std::fmt::Write as _;
+
+        let media_type = self.canonical_media_type();
+        let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+        // 以长度前缀分隔媒体类型与数据，避免不同的组合得到相同的输入
+        context.update(&(media_type.len() as u64).to_be_bytes());
+        context.update(media_type.as_bytes());
+        context.update(&self.data);
+
+        let mut hex = String::with_capacity(64);
+        for byte in context.finish().as_ref() {
+            let _ = write!(hex, "{:02x}", byte);
+        }
+        hex
+    }
+
+    /// 规范化的媒体类型，默认媒体类型为空字符串
+    fn canonical_media_type(&self) -> String {
+        let mut media_type = essence(&self.media_type).to_ascii_lowercase();
+        for span in params::param_spans(&self.media_type) {
+            let name = self.media_type[span.name].to_ascii_lowercase();
+            let mut value = params::unescape(&self.media_type[span.value]);
+            if name == "charset" {
+                value = value.to_ascii_lowercase().into();
+            }
+            media_type.push(';');
+            media_type.push_str(&name);
+            media_type.push('=');
+            media_type.push_str(&params::quote(&value));
+        }
+
+        let media_type = params::canonical_order(&media_type);
+        if media_type == "text/plain;charset=us-ascii" {
+            String::new()
+        } else {
+            media_type.into_owned()
+        }
+    }
+
+    /// 逗号之前的头部，如 `data:image/png;base64`
+    pub fn header(&self) -> String {
+        self.render().header()
+    }
+
+    /// 逗号之后编码后的数据部分，如直接传给只接受 base64 的浏览器 API
+    pub fn encoded_payload(&self) -> String {
+        self.render().encoded_payload()
+    }
+
+    /// 渲染为可嵌入 CSS 的 `url("...")`，见 [`Render::to_css_url`]
+    pub fn to_css_url(&self) -> String {
+        self.render().to_css_url()
+    }
+
+    /// 渲染为可嵌入 HTML 属性值的字符串，见 [`Render::to_html_attr`]
+    pub fn to_html_attr(&self) -> String {
+        self.render().to_html_attr()
+    }
+
+    /// 将 Data URL 直接写入 `w`，适合拼接大型 HTML 文档而无需为每个 Data URL 单独生成字符串
+    pub fn write_to(&self, w: &mut impl std::fmt::Write) -> std::fmt::Result {
+        self.render().write_to(w)
+    }
+
+    /// 将 Data URL 流式写入文件等 [`std::io::Write`]，见 [`Render::write_io`]
+    pub fn write_io(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        self.render().write_io(w)
+    }
+
+    /// 获取解码后的原始数据，与 `base64_encoded` 无关
+    pub fn decode(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    /// 以 UTF-8 文本借用数据，数据不是有效的 UTF-8 或 charset 不是 UTF-8 兼容编码时返回 `None`
+    ///
+    /// 需要区分失败原因时使用 [`into_string`](Self::into_string)
+    pub fn as_str(&self) -> Option<&str> {
+        self.check_utf8_charset().ok()?;
+        std::str::from_utf8(&self.data).ok()
+    }
+
+    /// 将数据转换为 UTF-8 字符串
+    ///
+    /// 未声明 charset 或为 `utf-8`、`us-ascii` 时校验 UTF-8，其他 charset 返回
+    /// [`DataUrlError::UnsupportedCharset`]，无效的字节返回 [`DataUrlError::Utf8`]
+    pub fn into_string(self) -> Result<String, DataUrlError> {
+        self.check_utf8_charset()?;
+        String::from_utf8(self.data).map_err(|e| DataUrlError::Utf8(e.utf8_error()))
+    }
+
+    /// 不支持转码，只接受与 UTF-8 兼容的 charset
+    fn check_utf8_charset(&self) -> Result<(), DataUrlError> {
+        match self.param("charset") {
+            Some(charset)
+                if !["utf-8", "utf8", "us-ascii"]
+                    .iter()
+                    .any(|utf8| charset.eq_ignore_ascii_case(utf8)) =>
+            {
+                Err(DataUrlError::UnsupportedCharset(charset.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 解码后数据的字节数
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 数据是否为空
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 渲染后的 Data URL 字符串长度是否不超过 `max_chars`
+    pub fn fits_within(&self, max_chars: usize) -> bool {
+        self.encoded_len() <= max_chars
+    }
+
+    /// 渲染后 Data URL 字符串的长度，与 `to_string().len()` 相同但不分配内存
+    pub fn encoded_len(&self) -> usize {
+        let payload = if self.base64_encoded {
+            self.base64_len()
+        } else {
+            self.percent_len()
+        };
+        let encoding = if self.base64_encoded {
+            ";base64".len()
+        } else {
+            0
+        };
+        let fragment = self
+            .fragment
+            .as_ref()
+            .map_or(0, |fragment| "#".len() + fragment.len());
+        let media_type = params::encode_values(params::canonical_order(&self.media_type)).len();
+        "data:".len() + media_type + encoding + ",".len() + payload + fragment
+    }
+
+    /// 两种编码下数据部分的长度与当前编码的开销，用于判断是否值得内联以及哪种编码更短
+    ///
+    /// 不分配内存；百分号编码的长度需要遍历一次 `data`
+    pub fn overhead(&self) -> Overhead {
+        let base64_chars = self.base64_len();
+        let percent_chars = self.percent_len();
+        Overhead {
+            raw_bytes: self.data.len(),
+            encoded_chars: self.encoded_len(),
+            payload_chars: if self.base64_encoded {
+                base64_chars
+            } else {
+                percent_chars
+            },
+            base64_chars,
+            percent_chars,
+        }
+    }
+
+    /// 以带填充的 base64 编码时数据部分的长度
+    fn base64_len(&self) -> usize {
+        base64::encoded_len(self.data.len(), true).unwrap_or(usize::MAX)
+    }
+
+    /// 以百分号编码时数据部分的长度
+    fn percent_len(&self) -> usize {
+        percent_encode(&self.data, DATA_ENCODE_SET)
+            .map(str::len)
+            .sum()
+    }
+
+    /// 将解码后的数据写入文件，返回实际写入的路径
+    ///
+    /// 如果 `path` 是目录，则写入该目录下的 `data.<扩展名>`，扩展名根据媒体类型推断
+    pub fn to_file(&self, path: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+        let mut path = path.as_ref().to_path_buf();
+        if path.is_dir() {
+            path.push(format!("data.{}", self.extension()));
+        }
+        std::fs::write(&path, &self.data)?;
+        Ok(path)
+    }
+
+    /// 根据媒体类型推断文件扩展名，无法推断时为 `bin`
+    fn extension(&self) -> &'static str {
+        let essence = essence(&self.media_type).to_ascii_lowercase();
+        // 常见类型优先使用惯用扩展名
+        match essence.as_str() {
+            "text/plain" => "txt",
+            "text/html" => "html",
+            "image/jpeg" => "jpg",
+            DEFAULT_MEDIA_TYPE => "bin",
+            essence => mime_guess::get_mime_extensions_str(essence)
+                .and_then(|extensions| extensions.first().copied())
+                .unwrap_or("bin"),
+        }
+    }
+}
+
+/// 拆分后尚未解码的 Data URL 各部分
+struct RawDataUrl<'a> {
+    media_type: &'a str,
+    base64_encoded: bool,
+    payload: &'a str,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> RawDataUrl<'a> {
+    fn split(input: &'a str) -> Result<Self, ParseError> {
+        let rest = input
+            .get(..5)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+            .map(|_| &input[5..])
+            .ok_or(ParseError::MissingScheme)?;
+
+        // 数据中的 `#` 必须转义为 `%23`，因此未转义的 `#` 总是片段的开始
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+
+        // 以第一个逗号分隔头部与数据
+        let (header, payload) = rest.split_once(',').ok_or(ParseError::MissingComma)?;
+
+        let (media_type, base64_encoded) = match header
+            .len()
+            .checked_sub(7)
+            // 按字节比较，多字节字符内部的位置不会被切分
+            .filter(|&at| header.as_bytes()[at..].eq_ignore_ascii_case(b";base64"))
+        {
+            Some(at) => (&header[..at], true),
+            None => (header, false),
+        };
+
+        Ok(Self {
+            media_type,
+            base64_encoded,
+            payload,
+            fragment,
+        })
+    }
+}
+
+/// 计算 Data URL 字符串解码后的字节数，不解码数据也不分配内存
+///
+/// base64 数据按字符数计算 (忽略空白，接受 URL 安全字母表)，字符与填充按 [`validate_data_url`] 的规则检查；
+/// 其他数据按百分号解码后的字节数计算
+pub fn decoded_size(data_url: &str) -> Result<usize, ParseError> {
+    let raw = RawDataUrl::split(data_url)?;
+    if !raw.base64_encoded {
+        return Ok(percent_decode_str(raw.payload).count());
+    }
+
+    let chars = validate_base64(raw.payload)?;
+    Ok(chars / 4 * 3 + (chars % 4).saturating_sub(1))
+}
+
+/// 是否为语法正确的 Data URL，见 [`validate_data_url`]
+pub fn is_data_url(input: &str) -> bool {
+    validate_data_url(input).is_ok()
+}
+
+/// 检查 Data URL 的语法而不解码数据：`data:` 前缀、可选的媒体类型、可选的 `;base64`、逗号与数据部分
+///
+/// 比 [`DataUrl::parse`] 更严格：媒体类型须为有效的 `type/subtype` (可省略)，百分号转义须跟两位十六进制数，
+/// base64 只接受有效字符且 `=` 只能出现在末尾；与解析一样忽略空白并接受 URL 安全字母表。空数据是合法的
+pub fn validate_data_url(input: &str) -> Result<(), ParseError> {
+    let raw = RawDataUrl::split(input)?;
+
+    if !raw.media_type.is_empty() {
+        let valid = if raw.media_type.starts_with(';') {
+            format!("text/plain{}", raw.media_type)
+                .parse::<Mime>()
+                .is_ok()
+        } else {
+            raw.media_type.parse::<Mime>().is_ok()
+        };
+        if !valid {
+            return Err(ParseError::InvalidMediaType(raw.media_type.to_string()));
+        }
+    }
+
+    let bytes = raw.payload.as_bytes();
+    if let Some(index) = bytes.iter().enumerate().position(|(index, &byte)| {
+        byte == b'%'
+            && !bytes
+                .get(index + 1..index + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    }) {
+        return Err(ParseError::InvalidPercentEscape(index));
+    }
+
+    if raw.base64_encoded {
+        validate_base64(raw.payload)?;
+    }
+    Ok(())
+}
+
+/// 检查 base64 字符与填充，不解码，返回填充以外的字符数
+fn validate_base64(payload: &str) -> Result<usize, ParseError> {
+    let invalid = |e| Err(ParseError::InvalidBase64(e));
+    let mut chars = 0;
+    let mut padding = 0;
+    for (index, byte) in percent_decode_str(payload)
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .enumerate()
+    {
+        match byte {
+            b'=' => padding += 1,
+            _ if padding > 0 => return invalid(base64::DecodeError::InvalidByte(index, byte)),
+            b if b.is_ascii_alphanumeric() || b"+/-_".contains(&b) => chars += 1,
+            _ => return invalid(base64::DecodeError::InvalidByte(index, byte)),
+        }
+    }
+    if chars % 4 == 1 {
+        return invalid(base64::DecodeError::InvalidLength(chars));
+    }
+    if padding > 0 && (padding > 2 || (chars + padding) % 4 != 0) {
+        return invalid(base64::DecodeError::InvalidPadding);
+    }
+    Ok(chars)
+}
+
+/// 宽松地解码 base64 数据：忽略空白 (含百分号编码的换行)，
+/// 同时接受标准与 URL 安全字母表，填充可有可无
+fn decode_base64(payload: &str) -> Result<Vec<u8>, ParseError> {
+    let normalized: Vec<u8> = percent_decode_str(payload)
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| match byte {
+            b'-' => b'+',
+            b'_' => b'/',
+            byte => byte,
+        })
+        .collect();
+    LENIENT_BASE64
+        .decode(normalized)
+        .map_err(ParseError::InvalidBase64)
+}
+
+/// 媒体类型中 `;` 之前的 `type/subtype` 部分
+pub(crate) fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or_default().trim()
+}
+
+impl FromStr for DataUrl {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for DataUrl {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::parse(s)
+    }
+}
+
+/// 解析结果不借用输入，与 `TryFrom<&str>` 等价
+impl TryFrom<String> for DataUrl {
+    type Error = ParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+/// 将 DataUrl 转换为字符串表示形式
+impl Display for DataUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.render().fmt(f)
+    }
+}
+
+/// 序列化为 `data:...` 字符串形式
+#[cfg(feature = "serde")]
+impl serde::Serialize for DataUrl {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// 从 `data:...` 字符串反序列化
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DataUrl {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// base64 的字母表与填充方式，用于 [`Render::base64_variant`] 与 [`DataUrl::parse_with`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Base64Variant {
+    /// 标准字母表 (`+` 与 `/`)，带 `=` 填充，浏览器普遍支持
+    #[default]
+    Standard,
+    /// 标准字母表，不带填充
+    StandardNoPad,
+    /// URL 安全字母表 (`-` 与 `_`)，带 `=` 填充
+    UrlSafe,
+    /// URL 安全字母表，不带填充
+    UrlSafeNoPad,
+}
+
+impl Base64Variant {
+    pub(crate) fn engine(self) -> &'static GeneralPurpose {
+        match self {
+            Self::Standard => &STANDARD,
+            Self::StandardNoPad => &STANDARD_NO_PAD,
+            Self::UrlSafe => &URL_SAFE,
+            Self::UrlSafeNoPad => &URL_SAFE_NO_PAD,
+        }
+    }
+
+    /// 同一字母表不带填充的变体
+    pub(crate) fn without_padding(self) -> Self {
+        match self {
+            Self::Standard | Self::StandardNoPad => Self::StandardNoPad,
+            Self::UrlSafe | Self::UrlSafeNoPad => Self::UrlSafeNoPad,
+        }
+    }
+}
+
+/// [`DataUrl::overhead`] 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overhead {
+    /// 解码后数据的字节数
+    pub raw_bytes: usize,
+    /// 按当前编码渲染后整个 Data URL 的字符数，与 [`DataUrl::encoded_len`] 相同
+    pub encoded_chars: usize,
+    /// 按当前编码时数据部分的字符数
+    pub payload_chars: usize,
+    /// 以 base64 编码时数据部分的字符数
+    pub base64_chars: usize,
+    /// 以百分号编码时数据部分的字符数
+    pub percent_chars: usize,
+}
+
+impl Overhead {
+    /// 当前编码的数据部分比原始数据多出的百分比，base64 约为 33%；数据为空时为 0
+    pub fn percentage(&self) -> f64 {
+        if self.raw_bytes == 0 {
+            return 0.0;
+        }
+        (self.payload_chars as f64 - self.raw_bytes as f64) / self.raw_bytes as f64 * 100.0
+    }
+
+    /// 以 base64 编码是否比百分号编码更短，计入头部的 `;base64`
+    pub fn base64_is_shorter(&self) -> bool {
+        self.base64_chars + ";base64".len() < self.percent_chars
+    }
+}
+
+/// 数据编码策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncodingStrategy {
+    /// 总是使用 base64 编码
+    #[default]
+    AlwaysBase64,
+    /// 总是使用百分号编码
+    AlwaysPercent,
+    /// 文本类型使用百分号编码，二进制类型使用 base64 编码
+    Auto,
+}
+
+impl EncodingStrategy {
+    /// 根据媒体类型判断是否使用 base64 编码
+    pub fn use_base64(&self, media_type: &str) -> bool {
+        match self {
+            Self::AlwaysBase64 => true,
+            Self::AlwaysPercent => false,
+            Self::Auto => !media_type
+                .parse::<Mime>()
+                .is_ok_and(|mime| is_text_mime(&mime)),
+        }
+    }
+}
+
+/// 是否为文本类型：`text/*`、JSON、XML (含 `+json`/`+xml` 后缀，如 `image/svg+xml`)
+pub(crate) fn is_text_mime(mime: &Mime) -> bool {
+    mime.type_() == mime::TEXT
+        || [mime::JSON, mime::XML].contains(&mime.subtype())
+        || mime
+            .suffix()
+            .is_some_and(|suffix| [mime::JSON, mime::XML].contains(&suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_data_url_creation() {
+        let data = DataUrl::new("text/plain".to_string(), b"Hello, World!".to_vec(), true);
+
+        assert_eq!(data.media_type, "text/plain");
+        assert!(data.base64_encoded);
+        assert_eq!(data.data, b"Hello, World!");
+
+        let expected_string = "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ==";
+        assert_eq!(data.to_string(), expected_string);
+    }
+
+    #[test]
+    fn test_try_new() {
+        let data = DataUrl::try_new("IMAGE/PNG", Vec::new(), true).unwrap();
+        assert_eq!(data.media_type, "image/png");
+
+        let data = DataUrl::try_new("Text/HTML; charset=utf-8", Vec::new(), false).unwrap();
+        assert_eq!(data.media_type, "text/html; charset=utf-8");
+
+        assert_eq!(
+            DataUrl::try_new("imagepng", Vec::new(), true),
+            Err(ParseError::InvalidMediaType("imagepng".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let data = DataUrl::from_bytes(b"abc".to_vec(), "text/plain", false);
+        assert_eq!(data, DataUrl::new("text/plain", b"abc".to_vec(), false));
+
+        let png = DataUrl::from_bytes_sniffed(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec());
+        assert_eq!(png.media_type, "image/png");
+        assert!(png.base64_encoded);
+
+        let unknown = DataUrl::from_bytes_sniffed(b"abc".to_vec());
+        assert_eq!(unknown.media_type, "application/octet-stream");
+    }
+
+    #[test]
+    fn test_standard_base64_alphabet() {
+        // 0xFB 0xFF 在标准字母表中编码为 `+/8=`，URL 安全字母表则为 `-_8`
+        let data = DataUrl::new("application/octet-stream", vec![0xFB, 0xFF], true);
+        assert_eq!(
+            data.to_string(),
+            "data:application/octet-stream;base64,+/8="
+        );
+    }
+
+    #[test]
+    fn test_parse_fragment() {
+        let data_url = DataUrl::parse("data:text/plain;base64,SGVsbG8=#greeting").unwrap();
+        assert_eq!(data_url.data, b"Hello");
+        assert_eq!(data_url.fragment(), Some("greeting"));
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;base64,SGVsbG8=#greeting"
+        );
+        assert_eq!(data_url.encoded_len(), data_url.to_string().len());
+
+        // 转义的 `%23` 属于数据
+        let data_url = DataUrl::parse("data:,a%23b#c").unwrap();
+        assert_eq!(data_url.data, b"a#b");
+        assert_eq!(data_url.fragment(), Some("c"));
+
+        let data_url = DataUrl::parse("data:,abc#").unwrap();
+        assert_eq!(data_url.data, b"abc");
+        assert_eq!(data_url.fragment(), Some(""));
+
+        assert_eq!(DataUrl::parse("data:,abc").unwrap().fragment(), None);
+    }
+
+    #[test]
+    fn test_percent_encode_unreserved() {
+        let data_url = DataUrl::new("text/plain", b"a-b_c.d~e".to_vec(), false);
+        assert_eq!(data_url.to_string(), "data:text/plain,a-b_c.d~e");
+
+        let data_url = DataUrl::new("text/plain", b"a b#c%d".to_vec(), false);
+        assert_eq!(data_url.to_string(), "data:text/plain,a%20b%23c%25d");
+    }
+
+    #[test]
+    fn test_from_text() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg"><path fill="#fff"/></svg>"##;
+        let data_url = DataUrl::from_text(svg, "image/svg+xml", Some("utf-8"));
+
+        assert_eq!(data_url.media_type, "image/svg+xml;charset=utf-8");
+        assert!(!data_url.base64_encoded);
+        let rendered = data_url.to_string();
+        assert!(rendered.starts_with("data:image/svg+xml;charset=utf-8,%3Csvg%20xmlns%3D%22"));
+        for c in ['<', '>', '#', '"', ' '] {
+            assert!(!rendered[rendered.find(',').unwrap()..].contains(c));
+        }
+        assert_eq!(DataUrl::parse(&rendered).unwrap().data, svg.as_bytes());
+
+        let data_url = DataUrl::from_text("hi", "text/plain", None);
+        assert_eq!(data_url.to_string(), "data:text/plain,hi");
+    }
+
+    #[test]
+    fn test_write_to() {
+        let image = DataUrl::new("image/png", vec![0x89, b'P', b'N', b'G'], true);
+        let text = DataUrl::new("text/plain", b"a b".to_vec(), false);
+
+        let mut html = String::from("<img src=\"");
+        image.write_to(&mut html).unwrap();
+        html.push_str("\"><a href=\"");
+        text.write_to(&mut html).unwrap();
+        html.push_str("\">");
+
+        assert_eq!(
+            html,
+            format!("<img src=\"{}\"><a href=\"{}\">", image, text)
+        );
+    }
+
+    #[test]
+    fn test_parse_base64() {
+        let data_url: DataUrl = "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ=="
+            .parse()
+            .unwrap();
+
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(data_url.base64_encoded);
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[test]
+    fn test_parse_lenient_base64() {
+        let bytes = vec![0xFB, 0xFF, 0xBF];
+
+        // 标准字母表，带填充
+        let data_url = DataUrl::parse("data:application/octet-stream;base64,+/+/").unwrap();
+        assert_eq!(data_url.data, bytes);
+
+        // URL 安全字母表，无填充
+        let data_url = DataUrl::parse("data:application/octet-stream;base64,-_8").unwrap();
+        assert_eq!(data_url.data, [0xFB, 0xFF]);
+
+        // 标准字母表，缺少填充
+        let data_url = DataUrl::parse("data:text/plain;base64,SGVsbG8sIFdvcmxkIQ").unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+
+        // 包含换行与空白
+        let data_url =
+            DataUrl::parse("data:text/plain;base64,SGVs\r\nbG8s IFdv\ncmxk\tIQ==").unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+
+        // 百分号编码的换行
+        let data_url =
+            DataUrl::parse("data:text/plain;base64,SGVsbG8s%0AIFdvcmxkIQ%3D%3D").unwrap();
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[test]
+    fn test_parse_percent_encoded() {
+        let data_url = DataUrl::parse("data:text/plain,Hello%2C%20World%21").unwrap();
+
+        assert_eq!(data_url.media_type, "text/plain");
+        assert!(!data_url.base64_encoded);
+        assert_eq!(data_url.data, b"Hello, World!");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let data = DataUrl::new("image/png", vec![0x89, b'P', b'N', b'G', 0x00, 0xFF], true);
+        assert_eq!(DataUrl::parse(&data.to_string()).unwrap(), data);
+
+        let data = DataUrl::new("text/plain", b"a b,c".to_vec(), false);
+        assert_eq!(DataUrl::parse(&data.to_string()).unwrap(), data);
+
+        // 所有字节值、两种编码、带参数与片段时都成立
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        for base64_encoded in [true, false] {
+            for data in [Vec::new(), b"%41;base64,#".to_vec(), all_bytes.clone()] {
+                let mut data_url =
+                    DataUrl::new("application/x-test;name=\"a;b\"", data, base64_encoded);
+                assert_eq!(DataUrl::parse(&data_url.to_string()).unwrap(), data_url);
+
+                data_url.fragment = Some("frag".to_string());
+                assert_eq!(DataUrl::parse(&data_url.to_string()).unwrap(), data_url);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let base64 = DataUrl::parse("data:image/png;base64,iVBORw==").unwrap();
+        assert_eq!(base64.decode(), vec![0x89, b'P', b'N', b'G']);
+
+        let percent = DataUrl::parse("data:image/png,%89PNG").unwrap();
+        assert_eq!(percent.decode(), base64.decode());
+    }
+
+    #[test]
+    fn test_empty_data() {
+        let base64 = DataUrl::new("text/plain", Vec::new(), true);
+        assert_eq!(base64.to_string(), "data:text/plain;base64,");
+        assert_eq!(DataUrl::parse("data:text/plain;base64,").unwrap(), base64);
+
+        let percent = DataUrl::new("text/plain", Vec::new(), false);
+        assert_eq!(percent.to_string(), "data:text/plain,");
+        assert_eq!(DataUrl::parse("data:text/plain,").unwrap(), percent);
+    }
+
+    #[test]
+    fn test_len() {
+        for data_url in [
+            DataUrl::new("text/plain", b"Hello, World!".to_vec(), true),
+            DataUrl::new("text/plain", b"Hello, World!".to_vec(), false),
+            DataUrl::new("image/png", vec![0x89, 0x00, 0xFF, 0x10], true),
+        ] {
+            assert_eq!(data_url.len(), data_url.data.len());
+            assert!(!data_url.is_empty());
+            assert_eq!(data_url.encoded_len(), data_url.to_string().len());
+        }
+
+        let empty = DataUrl::new("text/plain", Vec::new(), true);
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.encoded_len(), "data:text/plain;base64,".len());
+    }
+
+    #[test]
+    fn test_parse_with_variant() {
+        let data = vec![0xFB, 0xFF];
+        let url_safe = DataUrl::parse_with("data:;base64,-_8=", Base64Variant::UrlSafe).unwrap();
+        assert_eq!(url_safe.data, data);
+        assert!(DataUrl::parse_with("data:;base64,+/8=", Base64Variant::UrlSafe).is_err());
+        assert!(DataUrl::parse_with("data:;base64,-_8", Base64Variant::UrlSafe).is_err());
+
+        let no_pad = DataUrl::parse_with("data:;base64,+/8", Base64Variant::StandardNoPad).unwrap();
+        assert_eq!(no_pad.data, data);
+        assert!(DataUrl::parse_with("data:;base64,+/8=", Base64Variant::StandardNoPad).is_err());
+
+        // 默认的宽松解析接受所有变体
+        for input in ["+/8=", "+/8", "-_8=", "-_8"] {
+            let parsed = DataUrl::parse(&format!("data:;base64,{}", input)).unwrap();
+            assert_eq!(parsed.data, data);
+        }
+    }
+
+    #[test]
+    fn test_overhead() {
+        let text = DataUrl::new("text/plain", b"Hello, World!".to_vec(), false);
+        let overhead = text.overhead();
+        assert_eq!(overhead.raw_bytes, 13);
+        assert_eq!(overhead.percent_chars, "Hello%2C%20World%21".len());
+        assert_eq!(overhead.base64_chars, 20);
+        assert_eq!(overhead.payload_chars, overhead.percent_chars);
+        assert_eq!(overhead.encoded_chars, text.to_string().len());
+        assert!(!overhead.base64_is_shorter());
+
+        let binary = DataUrl::new("image/png", (0..=255).collect(), true);
+        let overhead = binary.overhead();
+        assert_eq!(overhead.base64_chars, 344);
+        assert_eq!(overhead.payload_chars, 344);
+        assert!(overhead.percent_chars > 600);
+        assert!(overhead.base64_is_shorter());
+        assert!((overhead.percentage() - 34.375).abs() < 1e-9);
+
+        assert_eq!(
+            DataUrl::new("text/plain", Vec::new(), true)
+                .overhead()
+                .percentage(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_fits_within() {
+        let data_url = DataUrl::new("text/plain", b"Hello, World!".to_vec(), true);
+        let len = data_url.to_string().len();
+
+        assert!(data_url.fits_within(len));
+        assert!(!data_url.fits_within(len - 1));
+    }
+
+    #[test]
+    fn test_parse_non_ascii_media_type() {
+        for (input, media_type) in [
+            ("data:éaaaaaa,x", "éaaaaaa"),
+            ("data:é;base64,eA==", "é"),
+            ("data:text/plain;name=文件,x", "text/plain;name=文件"),
+        ] {
+            let data_url = DataUrl::parse(input).unwrap();
+            assert_eq!(data_url.data, b"x");
+            assert_eq!(decoded_size(input), Ok(1));
+            assert!(DataUrl::parse_with(input, Base64Variant::Standard).is_ok());
+            // 非 ASCII 的媒体类型不是有效的 MIME 类型
+            assert_eq!(
+                validate_data_url(input),
+                Err(ParseError::InvalidMediaType(media_type.to_string()))
+            );
+        }
+        assert!(!DataUrl::parse("data:éaaaaaa,x").unwrap().base64_encoded);
+        assert!(DataUrl::parse("data:é;base64,eA==").unwrap().base64_encoded);
+    }
+
+    #[test]
+    fn test_parse_omitted_media_type() {
+        let data_url = DataUrl::parse("data:,Hi").unwrap();
+        assert_eq!(data_url.media_type, RFC_DEFAULT_MEDIA_TYPE);
+
+        let data_url = DataUrl::parse("data:;base64,SGk=").unwrap();
+        assert_eq!(data_url.media_type, RFC_DEFAULT_MEDIA_TYPE);
+        assert_eq!(data_url.data, b"Hi");
+
+        let data_url = DataUrl::parse("data:;charset=utf-8,Hi").unwrap();
+        assert_eq!(data_url.media_type, "text/plain;charset=utf-8");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            DataUrl::parse("http://example.com"),
+            Err(ParseError::MissingScheme)
+        );
+        assert_eq!(
+            DataUrl::parse("data:text/plain;base64"),
+            Err(ParseError::MissingComma)
+        );
+        assert!(matches!(
+            DataUrl::parse("data:text/plain;base64,@@@"),
+            Err(ParseError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    fn test_params() {
+        let mut data_url =
+            DataUrl::parse("data:text/plain;charset=utf-8; filename=\"a;b.txt\";x-flag,Hi")
+                .unwrap();
+        assert_eq!(
+            data_url.params(),
+            vec![("charset", "utf-8"), ("filename", "a;b.txt")]
+        );
+        assert_eq!(data_url.param("FileName"), Some("a;b.txt"));
+        assert_eq!(data_url.param("missing"), None);
+
+        data_url.set_param("charset", "us-ascii");
+        data_url.set_param("filename", "report.txt");
+        data_url.set_param("title", "Q1 report");
+        assert_eq!(
+            data_url.media_type,
+            "text/plain;charset=us-ascii; filename=report.txt;x-flag;title=\"Q1 report\""
+        );
+        assert_eq!(data_url.param("title"), Some("Q1 report"));
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;charset=us-ascii; filename=report.txt;x-flag;title=\"Q1%20report\",Hi"
+        );
+        assert_eq!(DataUrl::parse(&data_url.to_string()), Ok(data_url));
+
+        assert!(
+            DataUrl::new("image/png", Vec::new(), true)
+                .params()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_param_round_trip() {
+        for value in ["a,b", "x#y", "a#b c", "100%", "%2C", "a\tb"] {
+            let mut data_url = DataUrl::new("text/plain", b"Hi".to_vec(), false);
+            data_url.set_param("filename", value);
+            let rendered = data_url.to_string();
+            assert!(
+                !rendered[..rendered.len() - 3].contains([',', '#', ' ']),
+                "{}",
+                rendered
+            );
+            assert_eq!(data_url.encoded_len(), rendered.len());
+
+            let parsed = DataUrl::parse(&rendered).unwrap();
+            assert_eq!(parsed.param("filename"), Some(value), "{}", rendered);
+            assert_eq!(parsed.data, b"Hi");
+            assert_eq!(parsed, data_url);
+        }
+
+        let data_url = DataUrl::parse("data:text/plain;filename=a%2Cb;name=%E6%96%87,x").unwrap();
+        assert_eq!(data_url.param("filename"), Some("a,b"));
+        assert_eq!(data_url.param("name"), Some("文"));
+    }
+
+    #[test]
+    fn test_to_base64_and_percent() {
+        let percent = DataUrl::parse("data:text/plain;charset=utf-8,a%20b").unwrap();
+        let base64 = percent.to_base64();
+        assert_eq!(
+            base64.to_string(),
+            "data:text/plain;charset=utf-8;base64,YSBi"
+        );
+        assert_eq!(base64.data, percent.data);
+
+        let back = base64.to_percent();
+        assert_eq!(back, percent);
+        assert_eq!(back.to_string(), "data:text/plain;charset=utf-8,a%20b");
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        let a = DataUrl::parse("data:Text/HTML; Foo=\"bar\"; Charset=UTF-8,%3Cp%3E#top").unwrap();
+        let b = DataUrl::parse("data:text/html;charset=utf-8;foo=bar;base64,PHA+").unwrap();
+        assert_eq!(
+            a.canonical_string(),
+            "data:text/html;charset=utf-8;foo=bar;base64,PHA+"
+        );
+        assert_eq!(a.canonical_string(), b.canonical_string());
+
+        let quoted = DataUrl::new("text/plain;name=\"a \\\"b\\\"\"", b"x".to_vec(), false);
+        assert_eq!(
+            quoted.canonical_string(),
+            "data:text/plain;name=\"a \\\"b\\\"\";base64,eA=="
+        );
+
+        assert_eq!(
+            DataUrl::parse("data:,a").unwrap().canonical_string(),
+            "data:;base64,YQ=="
+        );
+        assert_eq!(
+            DataUrl::parse("data:;charset=US-ASCII;base64,YQ")
+                .unwrap()
+                .canonical_string(),
+            "data:;base64,YQ=="
+        );
+    }
+
+    #[test]
+    fn test_with_data() {
+        let data_url = DataUrl::parse("data:image/svg+xml;charset=utf-8,%3Csvg%3E#icon").unwrap();
+        let replaced = data_url.clone().with_data(b"<svg/>".to_vec());
+        assert_eq!(
+            replaced.to_string(),
+            "data:image/svg+xml;charset=utf-8,%3Csvg%2F%3E#icon"
+        );
+
+        let mut data_url = data_url.to_base64();
+        data_url.set_data(b"<svg/>".to_vec());
+        assert_eq!(
+            data_url.to_string(),
+            "data:image/svg+xml;charset=utf-8;base64,PHN2Zy8+#icon"
+        );
+    }
+
+    #[cfg(feature = "hash")]
+    #[test]
+    fn test_content_hash() {
+        let base64 = DataUrl::parse("data:text/plain;charset=UTF-8;base64,SGk=").unwrap();
+        let percent = DataUrl::parse("data:Text/Plain;charset=utf-8,H%69#top").unwrap();
+        assert_eq!(base64.content_hash(), percent.content_hash());
+        assert_eq!(base64.content_hash().len(), 64);
+        assert!(base64.content_hash().bytes().all(|b| b.is_ascii_hexdigit()));
+
+        let omitted = DataUrl::parse("data:,Hi").unwrap();
+        let explicit = DataUrl::parse("data:text/plain;charset=US-ASCII;base64,SGk").unwrap();
+        assert_eq!(omitted.content_hash(), explicit.content_hash());
+
+        assert_ne!(base64.content_hash(), omitted.content_hash());
+        assert_ne!(
+            base64.content_hash(),
+            base64.clone().with_data(b"Ho".to_vec()).content_hash()
+        );
+        // SHA-256 (0u64 ++ "")
+        assert_eq!(
+            DataUrl::new("", Vec::new(), true).content_hash(),
+            "af5570f5a1810b7af78caf4bc70a660f0df51e42baf91d4de5b2328de0e83dfc"
+        );
+    }
+
+    #[test]
+    fn test_decoded_size() {
+        for len in 0..16 {
+            let data = vec![0xA5u8; len];
+            for base64_encoded in [true, false] {
+                let data_url =
+                    DataUrl::new("application/octet-stream", data.clone(), base64_encoded);
+                assert_eq!(decoded_size(&data_url.to_string()), Ok(len));
+            }
+        }
+
+        assert_eq!(decoded_size("data:;base64,SGVsbG8"), Ok(5));
+        assert_eq!(decoded_size("data:;base64,SGVs%0AbG8=#frag"), Ok(5));
+        assert_eq!(decoded_size("data:,a%20b"), Ok(3));
+        assert!(matches!(
+            decoded_size("data:;base64,SGV@"),
+            Err(ParseError::InvalidBase64(_))
+        ));
+        assert!(matches!(
+            decoded_size("data:;base64,SGVsb"),
+            Err(ParseError::InvalidBase64(_))
+        ));
+        // `=` 只能作为末尾的填充，与解析的结果一致
+        for invalid in ["SG=k", "SGk==", "S==="] {
+            let input = format!("data:;base64,{}", invalid);
+            assert!(
+                matches!(decoded_size(&input), Err(ParseError::InvalidBase64(_))),
+                "{}",
+                invalid
+            );
+        }
+        assert!(matches!(
+            DataUrl::parse("data:;base64,SG=k"),
+            Err(ParseError::InvalidBase64(_))
+        ));
+        assert_eq!(
+            decoded_size("text/plain,abc"),
+            Err(ParseError::MissingScheme)
+        );
+    }
+
+    #[test]
+    fn test_validate_data_url() {
+        for valid in [
+            "data:,",
+            "data:;base64,",
+            "data:,Hello%2C%20World%21",
+            "data:text/plain;charset=utf-8;base64,SGk=",
+            "data:;charset=utf-8,hi",
+            "data:;base64,SGVs%0AbG8#frag",
+            "data:image/png;base64,-_8=",
+        ] {
+            assert_eq!(validate_data_url(valid), Ok(()), "{}", valid);
+            assert!(is_data_url(valid));
+        }
+
+        assert_eq!(
+            validate_data_url("text/plain,abc"),
+            Err(ParseError::MissingScheme)
+        );
+        assert_eq!(
+            validate_data_url("data:text/plain"),
+            Err(ParseError::MissingComma)
+        );
+        assert!(!is_data_url("data:;base64"));
+        assert_eq!(
+            validate_data_url("data:imagepng,abc"),
+            Err(ParseError::InvalidMediaType("imagepng".to_string()))
+        );
+        assert_eq!(
+            validate_data_url("data:,100%"),
+            Err(ParseError::InvalidPercentEscape(3))
+        );
+        assert_eq!(
+            validate_data_url("data:,%zz"),
+            Err(ParseError::InvalidPercentEscape(0))
+        );
+        for invalid in ["SGV@", "SGVsb", "SG=k", "SGk==", "S==="] {
+            let input = format!("data:;base64,{}", invalid);
+            assert!(
+                matches!(validate_data_url(&input), Err(ParseError::InvalidBase64(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_and_ord() {
+        use std::collections::HashSet;
+
+        let png = DataUrl::new("image/png", vec![1, 2, 3], true);
+        let gif = DataUrl::new("image/gif", vec![1, 2, 3], true);
+        let set: HashSet<_> = [png.clone(), gif.clone(), png.clone()]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+
+        let mut list = vec![png.clone(), gif.clone(), png.to_percent()];
+        list.sort();
+        assert_eq!(list, vec![gif, png.to_percent(), png]);
+    }
+
+    #[test]
+    fn test_try_from() {
+        fn convert<T: TryFrom<S>, S>(s: S) -> Result<T, T::Error> {
+            T::try_from(s)
+        }
+
+        let data_url: DataUrl = convert("data:text/plain,Hi").unwrap();
+        assert_eq!(data_url.data, b"Hi");
+        let data_url: DataUrl = convert("data:text/plain;base64,SGk=".to_string()).unwrap();
+        assert_eq!(data_url.data, b"Hi");
+
+        // 错误类型可以通过 `?` 转换为 Box<dyn Error>
+        fn boxed(s: &str) -> Result<DataUrl, Box<dyn std::error::Error>> {
+            Ok(DataUrl::try_from(s)?)
+        }
+        assert_eq!(
+            boxed("no scheme").unwrap_err().to_string(),
+            ParseError::MissingScheme.to_string()
+        );
+    }
+
+    #[test]
+    fn test_encoding_strategy() {
+        for text in [
+            "text/html; charset=utf-8",
+            "application/json",
+            "application/xml",
+            "image/svg+xml",
+        ] {
+            assert!(!EncodingStrategy::Auto.use_base64(text), "{}", text);
+        }
+        assert!(EncodingStrategy::Auto.use_base64("image/png"));
+        assert!(EncodingStrategy::AlwaysBase64.use_base64("text/plain"));
+        assert!(!EncodingStrategy::AlwaysPercent.use_base64("image/png"));
+    }
+
+    #[test]
+    fn test_mime_predicates() {
+        let image = DataUrl::new("image/png", Vec::new(), true);
+        assert_eq!(image.mime(), Some(mime::IMAGE_PNG));
+        assert!(image.is_image() && !image.is_text() && !image.is_application());
+
+        let text = DataUrl::new("text/plain; charset=utf-8", Vec::new(), false);
+        assert_eq!(text.mime().unwrap().essence_str(), "text/plain");
+        assert!(text.is_text() && !text.is_image());
+
+        let json = DataUrl::new("application/json", Vec::new(), false);
+        assert!(json.is_application() && !json.is_text());
+
+        let invalid = DataUrl::new("imagepng", Vec::new(), true);
+        assert_eq!(invalid.mime(), None);
+        assert!(!invalid.is_image() && !invalid.is_text() && !invalid.is_application());
+    }
+
+    #[test]
+    fn test_without_params() {
+        let data_url = DataUrl::new("image/svg+xml; charset=utf-8", b"<svg/>".to_vec(), false);
+        let stripped = data_url.without_params();
+
+        assert_eq!(stripped.media_type, "image/svg+xml");
+        assert_eq!(stripped.data, data_url.data);
+        assert_eq!(stripped.base64_encoded, data_url.base64_encoded);
+        assert_eq!(data_url.media_type, "image/svg+xml; charset=utf-8");
+    }
+
+    #[test]
+    fn test_charset() {
+        let data = DataUrl::new("text/html;charset=utf-8", Vec::new(), false);
+        assert_eq!(data.charset().as_deref(), Some("utf-8"));
+
+        let data = DataUrl::new("image/png", Vec::new(), true);
+        assert_eq!(data.charset(), None);
+    }
+
+    #[test]
+    fn test_into_string() {
+        let data = DataUrl::from_text("你好", "text/plain", Some("UTF-8"));
+        assert_eq!(data.as_str(), Some("你好"));
+        assert_eq!(data.into_string().unwrap(), "你好");
+
+        let data = DataUrl::new("application/json", b"{}".to_vec(), true);
+        assert_eq!(data.into_string().unwrap(), "{}");
+
+        let data = DataUrl::new("text/plain", vec![0xff, 0xfe], false);
+        assert_eq!(data.as_str(), None);
+        assert!(matches!(data.into_string(), Err(DataUrlError::Utf8(_))));
+
+        let data = DataUrl::new("text/plain;charset=\"GBK\"", b"abc".to_vec(), false);
+        assert_eq!(data.as_str(), None);
+        assert!(matches!(
+            data.into_string(),
+            Err(DataUrlError::UnsupportedCharset(charset)) if charset == "GBK"
+        ));
+    }
+
+    #[test]
+    fn test_to_file() {
+        let dir = std::env::temp_dir().join("get_data_url_test_to_file");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let data_url = DataUrl::parse("data:image/png;base64,iVBORw==").unwrap();
+        let written = data_url.to_file(&dir).unwrap();
+        assert_eq!(written, dir.join("data.png"));
+        assert_eq!(std::fs::read(&written).unwrap(), [0x89, b'P', b'N', b'G']);
+
+        let data_url = DataUrl::parse("data:text/plain,Hello").unwrap();
+        let written = data_url.to_file(dir.join("hello")).unwrap();
+        assert_eq!(written, dir.join("hello"));
+        assert_eq!(std::fs::read(&written).unwrap(), b"Hello");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let png = DataUrl::new(
+            "image/png",
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01".to_vec(),
+            true,
+        );
+
+        let json = serde_json::to_string(&png).unwrap();
+        assert_eq!(json, format!("\"{}\"", png));
+        assert_eq!(serde_json::from_str::<DataUrl>(&json).unwrap(), png);
+
+        assert!(serde_json::from_str::<DataUrl>("\"not a data url\"").is_err());
+        assert!(serde_json::from_str::<DataUrl>("42").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Display;
+
+/// Data URL 解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// 缺少 `data:` 前缀
+    MissingScheme,
+    /// 缺少分隔头部与数据的逗号
+    MissingComma,
+    /// base64 数据无效
+    InvalidBase64(base64::DecodeError),
+    /// 媒体类型不是有效的 `type/subtype`
+    InvalidMediaType(String),
+    /// 数据部分中 `%` 之后不是两位十六进制数，值为 `%` 的位置
+    InvalidPercentEscape(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingScheme => write!(f, "缺少 `data:` 前缀"),
+            Self::MissingComma => write!(f, "缺少分隔头部与数据的逗号"),
+            Self::InvalidBase64(e) => write!(f, "base64 数据无效: {}", e),
+            Self::InvalidMediaType(media_type) => write!(f, "无效的媒体类型: {}", media_type),
+            Self::InvalidPercentEscape(index) => write!(f, "位置 {} 的百分号转义无效", index),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidBase64(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 本库所有可失败操作的错误类型
+#[derive(Debug)]
+pub enum DataUrlError {
+    /// HTTP 请求失败
+    #[cfg(feature = "http")]
+    Http(reqwest::Error),
+    /// Data URL 格式错误
+    Parse(ParseError),
+    /// base64 解码失败
+    Decode(base64::DecodeError),
+    /// 数据大小超出限制
+    TooLarge { limit: usize, actual: usize },
+    /// 资源超出内联阈值，应改为普通 URL 引用
+    TooLargeToInline { url: String, size: u64 },
+    /// 批量获取的总字节数超出 [`GetDataUrl::with_total_byte_budget`](crate::GetDataUrl::with_total_byte_budget)
+    /// 的预算，`url` 为被跳过或中止的请求
+    BudgetExceeded { url: String, budget: usize },
+    /// 响应的媒体类型与期望不符，例如请求图片却得到 HTML 错误页
+    UnexpectedContentType { expected: String, got: String },
+    /// 媒体类型不在 [`GetDataUrl::with_allowed_types`](crate::GetDataUrl::with_allowed_types) 的允许列表中
+    DisallowedType { got: String },
+    /// 数据不是有效的 UTF-8 文本
+    Utf8(std::str::Utf8Error),
+    /// charset 不是 UTF-8 兼容编码，无法直接转换为字符串
+    UnsupportedCharset(String),
+    /// 渲染后的 Data URL 长度超出限制
+    TooLong { limit: usize, actual: usize },
+    /// 请求超时，包括下载响应体的时间
+    Timeout,
+    /// 建立连接超时，见 [`GetDataUrlBuilder::connect_timeout`](crate::GetDataUrlBuilder::connect_timeout)
+    ConnectTimeout,
+    /// 等待响应头 (首字节) 超时，见 [`GetDataUrl::with_read_timeout`](crate::GetDataUrl::with_read_timeout)
+    ///
+    /// 通常是服务器暂时繁忙，与 [`Timeout`](Self::Timeout) 不同，适合重试
+    ReadTimeout,
+    /// 请求被 [`GetDataUrl::fetch_cancellable`](crate::GetDataUrl::fetch_cancellable) 的令牌取消
+    Cancelled,
+    /// 文件读写失败
+    Io(std::io::Error),
+    /// 服务器返回了 4xx/5xx 状态码
+    Status { code: u16 },
+    /// URL 格式错误，未发出任何请求
+    #[cfg(feature = "http")]
+    InvalidUrl {
+        url: String,
+        source: url::ParseError,
+    },
+    /// URL 或重定向目标被 [`UrlPolicy`](crate::UrlPolicy) 拒绝，不会向该 URL 发出请求
+    Blocked { url: String },
+    /// 合并的并发请求失败，错误由多个调用者共享
+    Shared(std::sync::Arc<DataUrlError>),
+    /// 禁止重定向时服务器返回了重定向响应
+    Redirect {
+        status: u16,
+        location: Option<String>,
+    },
+    /// 重定向次数超出 [`RedirectPolicy::Limited`](crate::RedirectPolicy::Limited) 的限制或出现循环
+    ///
+    /// `chain` 依次为原始 URL、每一跳的 URL 以及被拒绝的下一跳
+    TooManyRedirects { chain: Vec<String> },
+}
+
+impl Display for DataUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "http")]
+            Self::Http(e) => write!(f, "HTTP 请求失败: {}", e),
+            Self::Parse(e) => write!(f, "Data URL 格式错误: {}", e),
+            Self::Decode(e) => write!(f, "base64 解码失败: {}", e),
+            Self::TooLarge { limit, actual } => {
+                write!(f, "数据大小 {} 字节超出限制 {} 字节", actual, limit)
+            }
+            Self::TooLargeToInline { url, size } => {
+                write!(f, "资源 {} 大小 {} 字节超出内联阈值", url, size)
+            }
+            Self::BudgetExceeded { url, budget } => {
+                write!(f, "批量获取超出总字节预算 {} 字节，已跳过 {}", budget, url)
+            }
+            Self::UnexpectedContentType { expected, got } => {
+                write!(f, "期望媒体类型 {}，实际为 {}", expected, got)
+            }
+            Self::DisallowedType { got } => write!(f, "媒体类型 {} 不在允许列表中", got),
+            Self::Utf8(e) => write!(f, "数据不是有效的 UTF-8 文本: {}", e),
+            Self::UnsupportedCharset(charset) => write!(f, "不支持的字符集: {}", charset),
+            Self::TooLong { limit, actual } => {
+                write!(f, "Data URL 长度 {} 字符超出限制 {} 字符", actual, limit)
+            }
+            Self::Timeout => write!(f, "请求超时"),
+            Self::ConnectTimeout => write!(f, "建立连接超时"),
+            Self::ReadTimeout => write!(f, "等待响应超时"),
+            Self::Cancelled => write!(f, "请求已取消"),
+            Self::Io(e) => write!(f, "文件读写失败: {}", e),
+            Self::Status { code } => write!(f, "服务器返回错误状态码 {}", code),
+            #[cfg(feature = "http")]
+            Self::InvalidUrl { url, source } => write!(f, "无效的 URL {:?}: {}", url, source),
+            Self::Blocked { url } => write!(f, "URL 被访问策略拒绝: {}", url),
+            Self::Shared(e) => write!(f, "合并的并发请求失败: {}", e),
+            Self::Redirect { status, location } => match location {
+                Some(location) => write!(f, "服务器返回重定向 {} 到 {}", status, location),
+                None => write!(f, "服务器返回重定向 {}", status),
+            },
+            Self::TooManyRedirects { chain } => {
+                write!(f, "重定向次数过多或出现循环: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataUrlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "http")]
+            Self::Http(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Decode(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Utf8(e) => Some(e),
+            #[cfg(feature = "http")]
+            Self::InvalidUrl { source, .. } => Some(source),
+            Self::Shared(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 重定向策略拒绝继续跟随时交给 reqwest 的错误，转换为 [`DataUrlError`] 时还原为
+/// [`DataUrlError::TooManyRedirects`]
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+#[derive(Debug)]
+pub(crate) struct RedirectChain(pub(crate) Vec<String>);
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl Display for RedirectChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "重定向次数过多或出现循环: {}", self.0.join(" -> "))
+    }
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl std::error::Error for RedirectChain {}
+
+/// 重定向目标被 [`UrlPolicy`](crate::UrlPolicy) 拒绝时交给 reqwest 的错误，转换为 [`DataUrlError`] 时还原为
+/// [`DataUrlError::Blocked`]
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+#[derive(Debug)]
+pub(crate) struct BlockedRedirect(pub(crate) String);
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl Display for BlockedRedirect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "重定向目标被 URL 策略拒绝: {}", self.0)
+    }
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+impl std::error::Error for BlockedRedirect {}
+
+/// 连接超时归入 `ConnectTimeout`，其他超时归入 `Timeout`，重定向策略拒绝的归入 `TooManyRedirects`，
+/// 重定向目标被 URL 策略拒绝的归入 `Blocked`，其余归入 `Http`
+#[cfg(feature = "http")]
+impl From<reqwest::Error> for DataUrlError {
+    fn from(e: reqwest::Error) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(e) = e.is_redirect().then(|| redirect_error(&e)).flatten() {
+            return e;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if e.is_timeout() && e.is_connect() {
+            return Self::ConnectTimeout;
+        }
+        if e.is_timeout() {
+            Self::Timeout
+        } else {
+            Self::Http(e)
+        }
+    }
+}
+
+/// 在错误链中查找 [`RedirectChain`] 或 [`BlockedRedirect`]
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+fn redirect_error(e: &reqwest::Error) -> Option<DataUrlError> {
+    let mut source = std::error::Error::source(e);
+    while let Some(e) = source {
+        if let Some(chain) = e.downcast_ref::<RedirectChain>() {
+            return Some(DataUrlError::TooManyRedirects {
+                chain: chain.0.clone(),
+            });
+        }
+        if let Some(blocked) = e.downcast_ref::<BlockedRedirect>() {
+            return Some(DataUrlError::Blocked {
+                url: blocked.0.clone(),
+            });
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// base64 解码错误归入 `Decode`，其余归入 `Parse`
+impl From<ParseError> for DataUrlError {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::InvalidBase64(e) => Self::Decode(e),
+            e => Self::Parse(e),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for DataUrlError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<std::io::Error> for DataUrlError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,117 @@
+use std::future::Future;
+
+#[cfg(feature = "test-util")]
+use std::collections::HashMap;
+
+use crate::{DataUrl, DataUrlError, GetDataUrl};
+
+/// 获取资源并转换为 DataUrl 的抽象，便于下游代码注入模拟实现进行测试
+///
+/// wasm32 上浏览器的 fetch 不是 `Send`，返回的 future 也不要求 `Send`
+pub trait Fetcher {
+    /// 从 URL 获取资源并转换为 DataUrl
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<DataUrl, DataUrlError>> + Send;
+
+    /// 从 URL 获取资源并转换为 DataUrl
+    #[cfg(target_arch = "wasm32")]
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<DataUrl, DataUrlError>>;
+}
+
+impl Fetcher for GetDataUrl {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<DataUrl, DataUrlError>> {
+        GetDataUrl::fetch(self, url)
+    }
+}
+
+/// 基于内存映射 (URL -> 数据与媒体类型) 的 [`Fetcher`]，需要启用 `test-util` feature
+///
+/// 未登记的 URL 返回 [`DataUrlError::Status`] (404)
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone, Default)]
+pub struct MapFetcher {
+    entries: HashMap<String, DataUrl>,
+}
+
+#[cfg(feature = "test-util")]
+impl MapFetcher {
+    /// 创建一个空的映射
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记 URL 对应的数据与媒体类型
+    pub fn insert(&mut self, url: impl Into<String>, media_type: impl Into<String>, data: Vec<u8>) {
+        self.entries
+            .insert(url.into(), DataUrl::new(media_type, data, true));
+    }
+
+    /// 登记 URL 对应的数据与媒体类型，返回自身以便链式调用
+    pub fn with(
+        mut self,
+        url: impl Into<String>,
+        media_type: impl Into<String>,
+        data: Vec<u8>,
+    ) -> Self {
+        self.insert(url, media_type, data);
+        self
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Fetcher for MapFetcher {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<DataUrl, DataUrlError>> {
+        let result = self
+            .entries
+            .get(url)
+            .cloned()
+            .ok_or(DataUrlError::Status { code: 404 });
+        std::future::ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn inline(fetcher: &impl Fetcher, url: &str) -> Result<String, DataUrlError> {
+        Ok(fetcher.fetch(url).await?.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_get_data_url_fetcher() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let result = inline(&GetDataUrl::new(), &mock_server.uri()).await;
+        assert_eq!(
+            result.unwrap(),
+            "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ=="
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn test_map_fetcher() {
+        let fetcher = MapFetcher::new().with(
+            "https://example.com/hello.txt",
+            "text/plain",
+            b"Hello, World!".to_vec(),
+        );
+
+        let result = inline(&fetcher, "https://example.com/hello.txt").await;
+        assert_eq!(
+            result.unwrap(),
+            "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ=="
+        );
+
+        let result = inline(&fetcher, "https://example.com/missing").await;
+        assert!(matches!(result, Err(DataUrlError::Status { code: 404 })));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Url;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// 按主机限制请求频率，避免批量内联同一服务器的资源时触发 429 或封禁
+///
+/// 通过 [`GetDataUrl::with_host_limit`] 启用，对同一转换器 (及其副本) 的所有请求生效，
+/// 包括 [`fetch_many`] 与 [`fetch_stream`]。主机按 `host:port` 区分
+///
+/// [`GetDataUrl::with_host_limit`]: crate::GetDataUrl::with_host_limit
+/// [`fetch_many`]: crate::GetDataUrl::fetch_many
+/// [`fetch_stream`]: crate::GetDataUrl::fetch_stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostLimit {
+    max_concurrent: Option<usize>,
+    min_delay: Option<Duration>,
+}
+
+impl HostLimit {
+    /// 创建不做任何限制的配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同一主机同时进行的请求数上限，最小为 1
+    pub fn max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = Some(max.max(1));
+        self
+    }
+
+    /// 同一主机相邻两个请求开始之间的最小间隔
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = Some(delay);
+        self
+    }
+}
+
+/// [`HostLimit`] 的运行时状态，在转换器的副本之间共享
+#[derive(Debug)]
+pub(crate) struct HostLimiter {
+    limit: HostLimit,
+    hosts: Mutex<HashMap<String, Arc<HostState>>>,
+}
+
+#[derive(Debug)]
+struct HostState {
+    semaphore: Option<Arc<Semaphore>>,
+    /// 下一个请求最早的开始时间
+    next: Mutex<Option<Instant>>,
+}
+
+impl HostLimiter {
+    /// wasm32 上无法获取当前时间，不支持按主机限制
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn new(limit: HostLimit) -> Self {
+        Self {
+            limit,
+            hosts: Mutex::default(),
+        }
+    }
+
+    /// 等待主机的并发名额与请求间隔，返回的名额在请求结束前应一直持有
+    pub(crate) async fn acquire(&self, url: &Url) -> Option<OwnedSemaphorePermit> {
+        let state = self.state(url);
+        let permit = match &state.semaphore {
+            Some(semaphore) => Some(
+                Arc::clone(semaphore)
+                    .acquire_owned()
+                    .await
+                    .expect("信号量不会被关闭"),
+            ),
+            None => None,
+        };
+
+        let start = {
+            let mut next = state.next.lock().unwrap();
+            let now = Instant::now();
+            let start = next.map_or(now, |next| next.max(now));
+            *next = Some(start + self.limit.min_delay.unwrap_or_default());
+            start
+        };
+        tokio::time::sleep_until(start).await;
+        permit
+    }
+
+    /// 服务器要求稍后重试时，推迟该主机之后的所有请求
+    pub(crate) fn pause(&self, url: &Url, delay: Duration) {
+        let state = self.state(url);
+        let mut next = state.next.lock().unwrap();
+        let until = Instant::now() + delay;
+        *next = Some(next.map_or(until, |next| next.max(until)));
+    }
+
+    fn state(&self, url: &Url) -> Arc<HostState> {
+        let key = format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        );
+        let mut hosts = self.hosts.lock().unwrap();
+        let state = hosts.entry(key).or_insert_with(|| {
+            Arc::new(HostState {
+                semaphore: self
+                    .limit
+                    .max_concurrent
+                    .map(|max| Arc::new(Semaphore::new(max))),
+                next: Mutex::default(),
+            })
+        });
+        Arc::clone(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_min_delay() {
+        let limiter = HostLimiter::new(HostLimit::new().min_delay(Duration::from_secs(1)));
+        let url = Url::parse("https://example.com/a.png").unwrap();
+        let other = Url::parse("https://example.org/a.png").unwrap();
+
+        let start = Instant::now();
+        limiter.acquire(&url).await;
+        limiter.acquire(&other).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire(&url).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        limiter.pause(&url, Duration::from_secs(5));
+        limiter.acquire(&url).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(6));
+    }
+}
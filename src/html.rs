@@ -0,0 +1,83 @@
+//! 生成嵌入 HTML 与 CSS 的代码片段，需要启用 `html` feature
+
+use std::fmt::Write as _;
+
+use crate::DataUrl;
+
+impl DataUrl {
+    /// 生成 `<img>` 标签，`src` 与其余属性值均经过转义
+    ///
+    /// 属性名包含空白、引号、`<`、`>`、`/`、`=` 等无效字符时被跳过；
+    /// `attrs` 中的 `src` 同样被跳过，以免覆盖数据
+    pub fn to_img_tag(&self, attrs: &[(&str, &str)]) -> String {
+        let mut tag = format!("<img src=\"{}\"", self.to_html_attr());
+        for (name, value) in attrs {
+            if !is_valid_attr_name(name) || name.eq_ignore_ascii_case("src") {
+                continue;
+            }
+            let _ = write!(tag, " {}=\"{}\"", name, escape_attr(value));
+        }
+        tag.push('>');
+        tag
+    }
+
+    /// 生成 CSS 声明 `background-image: url("...");`
+    pub fn to_css_background(&self) -> String {
+        format!("background-image: {};", self.to_css_url())
+    }
+}
+
+fn is_valid_attr_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| {
+            !c.is_whitespace()
+                && !c.is_control()
+                && !matches!(c, '"' | '\'' | '<' | '>' | '/' | '=')
+        })
+}
+
+/// 转义双引号属性值中的 `&`、`"`、`'`、`<`、`>`
+fn escape_attr(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_img_tag() {
+        let data_url = DataUrl::new("image/svg+xml;name=\"a b\"", b"<svg/>".to_vec(), false);
+        let tag = data_url.to_img_tag(&[
+            ("alt", "<script>alert(1)</script>"),
+            ("width", "16"),
+            ("onerror=\"x", "1"),
+            ("SRC", "https://evil.example/"),
+        ]);
+        assert_eq!(
+            tag,
+            "<img src=\"data:image/svg+xml;name=&quot;a%20b&quot;,%3Csvg%2F%3E\" \
+             alt=\"&lt;script&gt;alert(1)&lt;/script&gt;\" width=\"16\">"
+        );
+    }
+
+    #[test]
+    fn test_css_background() {
+        let data_url = DataUrl::new("image/png", b"png".to_vec(), true);
+        assert_eq!(
+            data_url.to_css_background(),
+            "background-image: url(\"data:image/png;base64,cG5n\");"
+        );
+    }
+}
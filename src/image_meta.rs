@@ -0,0 +1,94 @@
+//! 只解析文件头获取图片尺寸，需要启用 `image-meta` feature
+
+/// 根据 PNG IHDR、GIF 逻辑屏幕描述符或 JPEG SOFn 段获取图片的宽高
+pub(crate) fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    png(data).or_else(|| gif(data)).or_else(|| jpeg(data))
+}
+
+fn png(data: &[u8]) -> Option<(u32, u32)> {
+    let data = data.strip_prefix(b"\x89PNG\r\n\x1a\n")?;
+    // IHDR 必须是第一个块：长度 (4) + 类型 (4) + 宽 (4) + 高 (4)
+    if data.get(4..8)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(data.get(8..12)?.try_into().ok()?);
+    let height = u32::from_be_bytes(data.get(12..16)?.try_into().ok()?);
+    Some((width, height))
+}
+
+fn gif(data: &[u8]) -> Option<(u32, u32)> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return None;
+    }
+    let width = u16::from_le_bytes(data.get(6..8)?.try_into().ok()?);
+    let height = u16::from_le_bytes(data.get(8..10)?.try_into().ok()?);
+    Some((width.into(), height.into()))
+}
+
+fn jpeg(data: &[u8]) -> Option<(u32, u32)> {
+    let mut rest = data.strip_prefix(b"\xFF\xD8")?;
+    loop {
+        // 段以 0xFF 开头，之前可能有填充的 0xFF
+        let start = rest.iter().position(|&b| b != 0xFF)?;
+        if start == 0 {
+            return None;
+        }
+        let marker = rest[start];
+        rest = &rest[start + 1..];
+
+        match marker {
+            // 没有长度字段的独立标记
+            0x01 | 0xD0..=0xD7 => continue,
+            // 图像数据开始或结束之前都没有找到 SOFn
+            0xD9 | 0xDA => return None,
+            _ => {}
+        }
+
+        let length = usize::from(u16::from_be_bytes(rest.get(..2)?.try_into().ok()?));
+        // SOF0..SOF15，其中 0xC4 (DHT)、0xC8 (JPG)、0xCC (DAC) 不是帧头
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            // 长度 (2) + 精度 (1) + 高 (2) + 宽 (2)
+            let height = u16::from_be_bytes(rest.get(3..5)?.try_into().ok()?);
+            let width = u16::from_be_bytes(rest.get(5..7)?.try_into().ok()?);
+            return Some((width.into(), height.into()));
+        }
+        rest = rest.get(length..)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(dimensions(&png), Some((640, 480)));
+        assert_eq!(dimensions(&png[..20]), None);
+    }
+
+    #[test]
+    fn test_gif() {
+        assert_eq!(dimensions(b"GIF89a\x20\x03\x58\x02"), Some((800, 600)));
+    }
+
+    #[test]
+    fn test_jpeg() {
+        let jpeg = [
+            0xFF, 0xD8, // SOI
+            0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0
+            0xFF, 0xC4, 0x00, 0x02, // DHT
+            0xFF, 0xC2, 0x00, 0x0B, 0x08, 0x01, 0xE0, 0x02, 0x80, // SOF2: 640x480
+        ];
+        assert_eq!(dimensions(&jpeg), Some((640, 480)));
+        assert_eq!(dimensions(&[0xFF, 0xD8, 0xFF, 0xDA]), None);
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        assert_eq!(dimensions(b"<svg></svg>"), None);
+        assert_eq!(dimensions(b""), None);
+    }
+}
@@ -0,0 +1,46 @@
+use reqwest::Url;
+
+use crate::DataUrlError;
+
+/// 可以转换为 [`Url`] 的类型，供 [`GetDataUrl::fetch`] 等方法接收字符串或已解析的 URL
+///
+/// 字符串在发出请求前解析，格式错误时返回 [`DataUrlError::InvalidUrl`]
+///
+/// [`GetDataUrl::fetch`]: crate::GetDataUrl::fetch
+pub trait IntoUrl {
+    /// 转换为 [`Url`]
+    fn into_url(self) -> Result<Url, DataUrlError>;
+}
+
+impl IntoUrl for Url {
+    fn into_url(self) -> Result<Url, DataUrlError> {
+        Ok(self)
+    }
+}
+
+impl IntoUrl for &Url {
+    fn into_url(self) -> Result<Url, DataUrlError> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoUrl for &str {
+    fn into_url(self) -> Result<Url, DataUrlError> {
+        Url::parse(self).map_err(|source| DataUrlError::InvalidUrl {
+            url: self.to_string(),
+            source,
+        })
+    }
+}
+
+impl IntoUrl for &String {
+    fn into_url(self) -> Result<Url, DataUrlError> {
+        self.as_str().into_url()
+    }
+}
+
+impl IntoUrl for String {
+    fn into_url(self) -> Result<Url, DataUrlError> {
+        self.as_str().into_url()
+    }
+}
@@ -1,170 +1,92 @@
-use std::fmt::Display;
-
-use base64::{Engine as _, engine::general_purpose::STANDARD};
-use mime::Mime;
-use percent_encoding::{NON_ALPHANUMERIC, percent_encode};
-pub use reqwest::Error;
-use reqwest::{Client, header::CONTENT_TYPE};
-
-/// Data URL 结构体，表示一个符合 RFC 2397 标准的数据 URL
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DataUrl {
-    /// 媒体类型 (MIME type)
-    pub media_type: String,
-    /// 是否是 base64 编码
-    pub base64_encoded: bool,
-    /// 数据内容
-    pub data: Vec<u8>,
-}
-
-impl DataUrl {
-    /// 创建一个新的 DataUrl
-    pub fn new(media_type: impl Into<String>, data: Vec<u8>, base64_encoded: bool) -> Self {
-        Self {
-            media_type: media_type.into(),
-            base64_encoded,
-            data,
-        }
-    }
-}
-
-/// 将 DataUrl 转换为字符串表示形式
-impl Display for DataUrl {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let encoding = if self.base64_encoded { ";base64" } else { "" };
-        let data = if self.base64_encoded {
-            STANDARD.encode(&self.data)
-        } else {
-            // 对于非 base64 编码，需要确保数据是 URL 安全的
-            percent_encode(&self.data, NON_ALPHANUMERIC).to_string()
-        };
-        write!(f, "data:{}{},{}", self.media_type, encoding, data)
-    }
-}
-
-/// HTTP 到 Data URL 转换器
-#[derive(Debug, Clone)]
-pub struct GetDataUrl {
-    client: Client,
-}
-
-impl Default for GetDataUrl {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl GetDataUrl {
-    /// 创建一个新的转换器实例
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
-    }
-
-    /// 使用自定义 HTTP 客户端创建转换器实例
-    pub fn with_client(client: Client) -> Self {
-        Self { client }
-    }
-
-    /// 从 URL 获取资源并转换为 DataUrl
-    pub async fn fetch(&self, url: &str) -> Result<DataUrl, reqwest::Error> {
-        let response = self.client.get(url).send().await?;
-        println!("{:?}", response);
-        self.response_to_data_url(response).await
-    }
-
-    /// 将 HTTP 响应转换为 DataUrl
-    pub async fn response_to_data_url(
-        &self,
-        response: reqwest::Response,
-    ) -> Result<DataUrl, Error> {
-        // 获取内容类型
-        let content_type = response
-            .headers()
-            .get(CONTENT_TYPE)
-            .and_then(|value| value.to_str().ok())
-            .and_then(|value| value.parse::<Mime>().ok())
-            .map(|mime| mime.to_string())
-            .unwrap_or_else(|| "application/octet-stream".to_string());
-
-        // 读取响应字节
-        let bytes = response.bytes().await?.to_vec();
-
-        // 创建 DataUrl (总是使用 base64 编码以确保数据安全)
-        Ok(DataUrl::new(content_type, bytes, true))
-    }
+// wasm32 上只有一个线程，合并并发请求时保存的 future 不是 `Send`，共享配置仍沿用 `Arc`
+#![cfg_attr(target_arch = "wasm32", allow(clippy::arc_with_non_send_sync))]
+
+/// 调试日志，仅在启用 `log` feature 时输出
+#[cfg(feature = "http")]
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
 }
 
-/// 便捷函数：从 URL 获取资源并转换为 Data URL 字符串
-pub async fn url_to_data_url(url: &str) -> Result<String, Error> {
-    let converter = GetDataUrl::new();
-    let data_url = converter.fetch(url).await?;
-    Ok(data_url.to_string())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use wiremock::matchers::method;
-    use wiremock::{Mock, MockServer, ResponseTemplate};
-
-    #[tokio::test]
-    async fn test_data_url_creation() {
-        let data = DataUrl::new("text/plain".to_string(), b"Hello, World!".to_vec(), true);
-
-        assert_eq!(data.media_type, "text/plain");
-        assert!(data.base64_encoded);
-        assert_eq!(data.data, b"Hello, World!");
-
-        let expected_string = "data:text/plain;base64,SGVsbG8sIFdvcmxkIQ==";
-        assert_eq!(data.to_string(), expected_string);
-    }
-
-    #[tokio::test]
-    async fn test_fetch_data_url() {
-        let mock_server = MockServer::start().await;
-
-        // 设置模拟响应
-        Mock::given(method("GET"))
-            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
-            .mount(&mock_server)
-            .await;
-
-        let converter = GetDataUrl::new();
-        let result = converter.fetch(&mock_server.uri()).await;
-
-        assert!(result.is_ok());
-
-        let data_url = result.unwrap();
-        assert_eq!(data_url.media_type, "text/plain");
-        assert!(data_url.base64_encoded);
-        assert_eq!(data_url.data, b"Hello, World!");
-    }
-
-    #[tokio::test]
-    async fn test_url_to_data_url_convenience() {
-        let mock_server = MockServer::start().await;
-
-        Mock::given(method("GET"))
-            .respond_with(
-                ResponseTemplate::new(200).set_body_json(r#"{"message": "Hello, World!"}"#),
-            )
-            .mount(&mock_server)
-            .await;
-
-        let result = url_to_data_url(&mock_server.uri()).await;
-        assert!(result.is_ok());
-
-        let data_url_str = result.unwrap();
-        println!("{}", data_url_str);
-        assert!(data_url_str.starts_with("data:application/json;base64,"));
-    }
-
-    #[tokio::test]
-    async fn test_invalid_url() {
-        let converter = GetDataUrl::new();
-        let result = converter.fetch("not_a_valid_url").await;
-        assert!(result.is_err());
-    }
-}
+// reqwest 的阻塞客户端需要线程，wasm32 上只能使用异步的 GetDataUrl
+#[cfg(all(feature = "blocking", target_arch = "wasm32"))]
+compile_error!("`blocking` feature 不支持 wasm32，请使用异步的 `GetDataUrl`");
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "http")]
+mod builder;
+mod bundle;
+#[cfg(feature = "http")]
+mod cache;
+#[cfg(feature = "http")]
+mod client;
+mod codec;
+mod error;
+#[cfg(feature = "http")]
+mod fetcher;
+#[cfg(feature = "http")]
+mod host_limit;
+#[cfg(feature = "html")]
+mod html;
+#[cfg(feature = "image-meta")]
+mod image_meta;
+#[cfg(feature = "http")]
+mod into_url;
+#[cfg(feature = "http")]
+mod meta;
+mod params;
+#[cfg(feature = "http")]
+mod policy;
+mod render;
+#[cfg(feature = "http")]
+mod resolver;
+#[cfg(feature = "http")]
+mod single_flight;
+mod sniff;
+#[cfg(feature = "http")]
+mod source;
+
+#[cfg(feature = "http")]
+pub use builder::GetDataUrlBuilder;
+pub use bundle::bundle;
+#[cfg(feature = "http")]
+pub use cache::{Cache, CacheEntry, MemoryCache};
+#[cfg(feature = "http")]
+pub use client::{
+    DEFAULT_CONCURRENCY, DEFAULT_MAX_RETRY_AFTER, DEFAULT_MAX_SIZE, DEFAULT_RETRY_DELAY,
+    GetDataUrl, RedirectPolicy, url_to_data_url,
+};
+use codec::DATA_ENCODE_SET;
+pub use codec::{
+    Base64Variant, DEFAULT_MEDIA_TYPE, DataUrl, EncodingStrategy, Overhead, RFC_DEFAULT_MEDIA_TYPE,
+    decoded_size, is_data_url, validate_data_url,
+};
+#[cfg(feature = "http")]
+use codec::{essence, is_text_mime};
+pub use error::{DataUrlError, ParseError};
+#[cfg(feature = "http")]
+pub use fetcher::Fetcher;
+#[cfg(feature = "test-util")]
+pub use fetcher::MapFetcher;
+#[cfg(feature = "http")]
+pub use host_limit::HostLimit;
+#[cfg(feature = "http")]
+use host_limit::HostLimiter;
+#[cfg(feature = "http")]
+pub use into_url::IntoUrl;
+#[cfg(feature = "http")]
+pub use meta::{AssetRef, FetchMeta, ValidationReport};
+#[cfg(feature = "http")]
+pub use policy::UrlPolicy;
+pub use render::Render;
+#[cfg(feature = "http")]
+pub use resolver::{ContentTypeResolver, ContentTypeSource};
+#[cfg(feature = "http")]
+use single_flight::SingleFlight;
+#[cfg(feature = "http")]
+pub use source::Source;
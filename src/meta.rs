@@ -0,0 +1,99 @@
+use reqwest::header::{CONTENT_ENCODING, HeaderMap};
+use reqwest::{Response, Url};
+
+use crate::DataUrl;
+
+/// 获取资源时的响应元数据，由 [`GetDataUrl::fetch_with_meta`] 返回
+///
+/// [`GetDataUrl::fetch_with_meta`]: crate::GetDataUrl::fetch_with_meta
+#[derive(Debug, Clone)]
+pub struct FetchMeta {
+    /// 跟随重定向后的最终 URL
+    pub final_url: Url,
+    /// 最终响应的状态码，命中缓存时为 304
+    pub status: u16,
+    /// 响应头中的 Content-Length，未提供时为 `None`
+    pub content_length: Option<u64>,
+    /// 响应的 Content-Encoding；启用自动解压 (默认) 时已解压，总是为 `None`
+    pub content_encoding: Option<String>,
+    /// 最终响应的全部响应头
+    pub headers: HeaderMap,
+    /// 发送请求的次数，包括重试
+    pub attempts: u32,
+}
+
+/// URL 的预检结果，由 [`GetDataUrl::validate`] 返回
+///
+/// [`GetDataUrl::validate`]: crate::GetDataUrl::validate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// 检测到的媒体类型，无法确定时为默认类型
+    pub media_type: String,
+    /// 媒体类型是否来自 Content-Type 或嗅探，而不是默认类型
+    pub recognized: bool,
+    /// 资源大小 (字节)；超出限制时为 Content-Length 或停止读取前已读取的字节数
+    pub size: u64,
+    /// 是否超出 `max_size`
+    pub too_large: bool,
+    /// 媒体类型是否在 `allowed_types` 允许的范围内，未设置时总为 `true`
+    pub allowed: bool,
+}
+
+impl ValidationReport {
+    /// 是否可以内联：媒体类型已识别且被允许，大小未超出限制
+    pub fn is_valid(&self) -> bool {
+        self.recognized && self.allowed && !self.too_large
+    }
+}
+
+/// [`GetDataUrl::fetch_or_link`] 的结果：小资源内联，大资源保留为链接
+///
+/// [`GetDataUrl::fetch_or_link`]: crate::GetDataUrl::fetch_or_link
+#[derive(Debug, Clone)]
+pub enum AssetRef {
+    /// 资源不超过阈值，已转换为 DataUrl
+    Inlined(DataUrl),
+    /// 资源超出阈值，调用方应引用原始地址
+    Linked {
+        /// 跟随重定向后的最终 URL
+        url: Url,
+        /// 响应的媒体类型，缺失时为默认类型，可用于设置 `type` 等属性
+        media_type: String,
+        /// 响应元数据
+        meta: Box<FetchMeta>,
+    },
+}
+
+impl FetchMeta {
+    pub(crate) fn new(response: &Response, attempts: u32) -> Self {
+        Self {
+            final_url: response.url().clone(),
+            status: response.status().as_u16(),
+            content_length: response.content_length(),
+            content_encoding: response
+                .headers()
+                .get(CONTENT_ENCODING)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            headers: response.headers().clone(),
+            attempts,
+        }
+    }
+
+    /// 响应的媒体类型，解析规则与 [`GetDataUrl::fetch`] 相同
+    ///
+    /// [`GetDataUrl::fetch`]: crate::GetDataUrl::fetch
+    pub fn content_type(&self) -> Option<String> {
+        crate::client::content_type(&self.headers)
+    }
+
+    /// 最终 (跟随重定向后的) URL 是否使用 HTTPS，可用于拒绝内联明文传输的资源
+    pub fn is_https(&self) -> bool {
+        self.final_url.scheme() == "https"
+    }
+
+    /// 是否经过了重定向，即最终 URL 与请求的 URL 不同
+    pub fn redirected(&self, requested: &str) -> bool {
+        Url::parse(requested).map_or(true, |requested| requested != self.final_url)
+    }
+}
@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use percent_encoding::percent_decode_str;
+
+/// 媒体类型中一个 `name=value` 参数在字符串中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ParamSpan {
+    pub(crate) name: Range<usize>,
+    /// 值的位置，带引号的值包含两侧的引号
+    pub(crate) value: Range<usize>,
+}
+
+/// 按出现顺序找出媒体类型中的所有参数，忽略引号内的 `;` 以及没有 `=` 的片段
+pub(crate) fn param_spans(media_type: &str) -> Vec<ParamSpan> {
+    let mut spans = Vec::new();
+    for Range { start, end } in segments(media_type) {
+        let Some(eq) = media_type[start..end].find('=').map(|eq| start + eq) else {
+            continue;
+        };
+        let name = trim(media_type, start..eq);
+        if name.is_empty() {
+            continue;
+        }
+        spans.push(ParamSpan {
+            name,
+            value: trim(media_type, eq + 1..end),
+        });
+    }
+    spans
+}
+
+/// 类型之后以 `;` 分隔的各个片段 (不含 `;`)，忽略引号内的 `;`
+fn segments(media_type: &str) -> Vec<Range<usize>> {
+    let mut separators = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, b) in media_type.bytes().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' if in_quotes => escaped = true,
+            b'"' => in_quotes = !in_quotes,
+            b';' if !in_quotes => separators.push(i),
+            _ => {}
+        }
+    }
+
+    separators
+        .iter()
+        .enumerate()
+        .map(|(index, &separator)| {
+            let end = separators
+                .get(index + 1)
+                .copied()
+                .unwrap_or(media_type.len());
+            separator + 1..end
+        })
+        .collect()
+}
+
+/// 去掉媒体类型与参数两侧多余的空白，参数之间统一以 `; ` 分隔
+///
+/// 例如 `text/html ;charset = UTF-8` 变为 `text/html; charset=UTF-8`，没有 `=` 的片段会被丢弃
+#[cfg(feature = "http")]
+pub(crate) fn normalize_spacing(media_type: &str) -> String {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    let mut normalized = essence.to_string();
+    for span in param_spans(media_type) {
+        normalized.push_str("; ");
+        normalized.push_str(&media_type[span.name]);
+        normalized.push('=');
+        normalized.push_str(&media_type[span.value]);
+    }
+    normalized
+}
+
+/// 去掉名称匹配 (不区分大小写) 的参数及其前面的 `;`
+#[cfg(feature = "http")]
+pub(crate) fn remove_param(media_type: &str, name: &str) -> String {
+    let mut result = media_type.to_string();
+    for span in param_spans(media_type).into_iter().rev() {
+        if media_type[span.name.clone()].eq_ignore_ascii_case(name) {
+            let start = media_type[..span.name.start].rfind(';').unwrap_or(0);
+            result.replace_range(start..span.value.end, "");
+        }
+    }
+    result
+}
+
+/// 按规范顺序排列参数：`charset` 在前，其余按名称字母顺序 (不区分大小写)，同名参数保持原有顺序
+///
+/// 已经是规范顺序时原样返回；否则以 `;` 重新拼接，没有 `=` 的片段按原有顺序放在最后
+pub(crate) fn canonical_order(media_type: &str) -> Cow<'_, str> {
+    let spans = param_spans(media_type);
+    let key = |span: &ParamSpan| {
+        let name = media_type[span.name.clone()].to_ascii_lowercase();
+        (name != "charset", name)
+    };
+    if spans.windows(2).all(|pair| key(&pair[0]) <= key(&pair[1])) {
+        return Cow::Borrowed(media_type);
+    }
+
+    let mut sorted = spans;
+    sorted.sort_by_cached_key(key);
+    let mut canonical = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    for span in sorted {
+        canonical.push(';');
+        canonical.push_str(&media_type[span.name]);
+        canonical.push('=');
+        canonical.push_str(&media_type[span.value]);
+    }
+    for segment in segments(media_type) {
+        let segment = media_type[segment].trim();
+        if !segment.is_empty() && !segment.contains('=') {
+            canonical.push(';');
+            canonical.push_str(segment);
+        }
+    }
+    Cow::Owned(canonical)
+}
+
+/// 对参数值中的 `%`、`,`、`#` 与空白进行百分号编码，使其可以放在 Data URL 的头部
+///
+/// 未编码时 `,` 会被当作数据的开始，`#` 会被当作片段的开始；[`decode_values`] 为其逆操作
+pub(crate) fn encode_values(media_type: Cow<'_, str>) -> Cow<'_, str> {
+    let needs_encoding = |c: char| matches!(c, '%' | ',' | '#') || c.is_ascii_whitespace();
+    let spans = param_spans(&media_type);
+    if !spans
+        .iter()
+        .any(|span| media_type[span.value.clone()].contains(needs_encoding))
+    {
+        return media_type;
+    }
+
+    let mut encoded = media_type.to_string();
+    for span in spans.into_iter().rev() {
+        let mut value = String::with_capacity(span.value.len());
+        for c in media_type[span.value.clone()].chars() {
+            if needs_encoding(c) {
+                value.push_str(&format!("%{:02X}", c as u8));
+            } else {
+                value.push(c);
+            }
+        }
+        encoded.replace_range(span.value, &value);
+    }
+    Cow::Owned(encoded)
+}
+
+/// 对参数值进行百分号解码，解码后包含特殊字符的值按需加上引号
+///
+/// 解码结果不是有效的 UTF-8 时保持原样
+pub(crate) fn decode_values(media_type: &str) -> Cow<'_, str> {
+    let mut decoded = Cow::Borrowed(media_type);
+    for span in param_spans(media_type).into_iter().rev() {
+        let raw = &media_type[span.value.clone()];
+        // 没有可解码的转义时 decode_utf8 返回 Borrowed
+        if let Ok(Cow::Owned(value)) = percent_decode_str(&unescape(raw)).decode_utf8() {
+            decoded.to_mut().replace_range(span.value, &quote(&value));
+        }
+    }
+    decoded
+}
+
+/// 去掉值两侧的引号，引号内的转义符保持原样
+pub(crate) fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// 去掉值两侧的引号并还原引号内的转义符，是 [`quote`] 的逆操作
+pub(crate) fn unescape(value: &str) -> Cow<'_, str> {
+    let inner = unquote(value);
+    if inner.len() == value.len() || !inner.contains('\\') {
+        return Cow::Borrowed(inner);
+    }
+
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// 值为空或包含 token 以外的字符时加上引号
+pub(crate) fn quote(value: &str) -> String {
+    let is_token = !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&b));
+    if is_token {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 去掉范围两端的空白
+fn trim(s: &str, range: Range<usize>) -> Range<usize> {
+    let part = &s[range.clone()];
+    let start = range.start + (part.len() - part.trim_start().len());
+    let end = range.end - (part.len() - part.trim_end().len());
+    start..end.max(start)
+}
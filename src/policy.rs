@@ -0,0 +1,210 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use reqwest::Url;
+
+use crate::DataUrlError;
+
+/// URL 访问策略，在发出请求前检查协议与主机，用于防范 SSRF
+///
+/// 默认只允许 `http` 和 `https`，不限制主机。请求的 URL 与每个重定向目标都会被检查，
+/// 但只检查主机字面量，不会解析 DNS；通过 [`GetDataUrl::with_client`] 传入的自定义客户端
+/// 自行跟随重定向，此时重定向目标不受检查，如需完全防范请在该客户端上关闭重定向
+///
+/// [`GetDataUrl::with_client`]: crate::GetDataUrl::with_client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlPolicy {
+    schemes: Vec<String>,
+    allowed_hosts: Option<Vec<String>>,
+    denied_hosts: Vec<String>,
+    block_private_ips: bool,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UrlPolicy {
+    /// 创建默认策略：只允许 `http` 和 `https`
+    pub fn new() -> Self {
+        Self {
+            schemes: vec!["http".to_string(), "https".to_string()],
+            allowed_hosts: None,
+            denied_hosts: Vec::new(),
+            block_private_ips: false,
+        }
+    }
+
+    /// 设置允许的协议，替换默认的 `http`/`https`
+    pub fn allow_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.schemes = schemes
+            .into_iter()
+            .map(|scheme| scheme.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// 只允许访问指定的主机，不区分大小写
+    pub fn allow_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_hosts = Some(normalize_hosts(hosts));
+        self
+    }
+
+    /// 禁止访问指定的主机，不区分大小写，优先于 [`allow_hosts`](Self::allow_hosts)
+    pub fn deny_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denied_hosts = normalize_hosts(hosts);
+        self
+    }
+
+    /// 设置是否禁止访问 `localhost` 及回环、私有、运营商级 NAT、链路本地等内网地址，默认不禁止
+    ///
+    /// 以 IPv4 映射或兼容形式写成的 IPv6 地址 (如 `[::ffff:10.0.0.1]`) 按其中的 IPv4 地址判断
+    pub fn block_private_ips(mut self, block: bool) -> Self {
+        self.block_private_ips = block;
+        self
+    }
+
+    /// 检查 URL 是否被策略允许，不允许时返回 [`DataUrlError::Blocked`]
+    pub(crate) fn check(&self, url: &Url) -> Result<(), DataUrlError> {
+        if self.is_allowed(url) {
+            Ok(())
+        } else {
+            Err(DataUrlError::Blocked {
+                url: url.to_string(),
+            })
+        }
+    }
+
+    fn is_allowed(&self, url: &Url) -> bool {
+        if !self.schemes.iter().any(|scheme| scheme == url.scheme()) {
+            return false;
+        }
+
+        let Some(host) = url.host_str() else {
+            // 没有主机的 URL 只受协议限制
+            return self.allowed_hosts.is_none();
+        };
+        let host = host.to_ascii_lowercase();
+
+        if self.denied_hosts.contains(&host) {
+            return false;
+        }
+        if let Some(allowed) = &self.allowed_hosts
+            && !allowed.contains(&host)
+        {
+            return false;
+        }
+        !(self.block_private_ips && is_private_host(&host))
+    }
+}
+
+/// 主机名不区分大小写，统一转为小写
+fn normalize_hosts<I, S>(hosts: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    hosts
+        .into_iter()
+        .map(|host| host.into().to_ascii_lowercase())
+        .collect()
+}
+
+/// 主机是否为 `localhost` 或内网 IP 字面量，IPv6 地址带有方括号
+fn is_private_host(host: &str) -> bool {
+    let ip = host.trim_start_matches('[').trim_end_matches(']');
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => is_private_ipv4(ip),
+        Ok(IpAddr::V6(ip)) => is_private_ipv6(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.');
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+    }
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        // 100.64.0.0/10 运营商级 NAT 共享地址
+        || (first == 100 && (second & 0xc0) == 64)
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    if ip.is_loopback() || ip.is_unspecified() {
+        return true;
+    }
+    // ::ffff:a.b.c.d 映射地址与已废弃的 ::a.b.c.d 兼容地址
+    if let Some(ip) = ip.to_ipv4() {
+        return is_private_ipv4(ip);
+    }
+    let first = ip.segments()[0];
+    // fc00::/7 唯一本地地址，fe80::/10 链路本地地址
+    (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(policy: &UrlPolicy, url: &str) -> bool {
+        policy.is_allowed(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = UrlPolicy::default();
+        assert!(allowed(&policy, "https://example.com/a.png"));
+        assert!(allowed(&policy, "http://127.0.0.1:8080/"));
+        assert!(!allowed(&policy, "ftp://example.com/a.png"));
+        assert!(!allowed(&policy, "file:///etc/passwd"));
+    }
+
+    #[test]
+    fn test_private_ips() {
+        let policy = UrlPolicy::new().block_private_ips(true);
+        assert!(!allowed(&policy, "http://localhost/"));
+        assert!(!allowed(&policy, "http://127.0.0.1/"));
+        assert!(!allowed(&policy, "http://10.1.2.3/"));
+        assert!(!allowed(&policy, "http://169.254.169.254/latest/meta-data"));
+        assert!(!allowed(&policy, "http://[::1]/"));
+        assert!(!allowed(&policy, "http://[::ffff:192.168.0.1]/"));
+        assert!(!allowed(&policy, "http://[fd00::1]/"));
+        assert!(!allowed(&policy, "http://100.64.0.1/"));
+        assert!(!allowed(&policy, "http://100.127.255.254/"));
+        assert!(!allowed(&policy, "http://[::ffff:127.0.0.1]/"));
+        assert!(!allowed(&policy, "http://[::ffff:100.64.0.1]/"));
+        assert!(!allowed(&policy, "http://[::10.0.0.1]/"));
+        assert!(allowed(&policy, "http://100.128.0.1/"));
+        assert!(allowed(&policy, "http://[::ffff:93.184.216.34]/"));
+        assert!(allowed(&policy, "http://93.184.216.34/"));
+        assert!(allowed(&policy, "https://example.com/"));
+    }
+
+    #[test]
+    fn test_host_lists() {
+        let policy = UrlPolicy::new()
+            .allow_hosts(["CDN.example.com", "img.example.com"])
+            .deny_hosts(["img.example.com"]);
+        assert!(allowed(&policy, "https://cdn.example.com/a.png"));
+        assert!(!allowed(&policy, "https://img.example.com/a.png"));
+        assert!(!allowed(&policy, "https://other.example.com/a.png"));
+    }
+}
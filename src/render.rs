@@ -0,0 +1,465 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Write as _};
+
+use base64::display::Base64Display;
+use mime::Mime;
+use percent_encoding::percent_encode;
+
+use crate::{Base64Variant, DATA_ENCODE_SET, DataUrl, params};
+
+/// DataUrl 的渲染选项，通过 [`DataUrl::render`] 创建，实现 [`Display`]
+#[derive(Debug, Clone, Copy)]
+pub struct Render<'a> {
+    data_url: &'a DataUrl,
+    omit_default_media_type: bool,
+    line_wrap: Option<usize>,
+    padding: bool,
+    base64_variant: Base64Variant,
+}
+
+impl<'a> Render<'a> {
+    pub(crate) fn new(data_url: &'a DataUrl) -> Self {
+        Self {
+            data_url,
+            omit_default_media_type: false,
+            line_wrap: None,
+            padding: true,
+            base64_variant: Base64Variant::Standard,
+        }
+    }
+
+    /// 媒体类型为 RFC 2397 默认值 `text/plain;charset=US-ASCII` 时是否省略，默认不省略
+    ///
+    /// 省略后输出 `data:;base64,...` 或 `data:,...` 这样更短的形式
+    pub fn omit_default_media_type(mut self, omit: bool) -> Self {
+        self.omit_default_media_type = omit;
+        self
+    }
+
+    /// base64 数据每隔多少个字符插入一个 `\r\n`，默认为 `None` (单行，适合浏览器)
+    ///
+    /// 邮件 MIME 等场景通常要求 76 个字符换行；百分号编码的数据不受影响
+    pub fn line_wrap(mut self, width: Option<usize>) -> Self {
+        self.line_wrap = width.filter(|&width| width > 0);
+        self
+    }
+
+    /// base64 数据末尾是否输出 `=` 填充，默认输出
+    ///
+    /// 不带填充的形式更短，但部分严格的解析器不接受；本库解析时两种形式均可
+    pub fn padding(mut self, padding: bool) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// base64 数据使用的字母表与填充方式，默认为 [`Base64Variant::Standard`]
+    ///
+    /// 与 [`padding(false)`](Self::padding) 同时使用时不输出填充；本库解析时接受所有变体
+    pub fn base64_variant(mut self, variant: Base64Variant) -> Self {
+        self.base64_variant = variant;
+        self
+    }
+
+    /// 将 Data URL 直接写入 `w`，不分配中间字符串
+    pub fn write_to(&self, w: &mut impl std::fmt::Write) -> std::fmt::Result {
+        write!(w, "{}", self)
+    }
+
+    /// 将 Data URL 流式写入文件、socket 等 [`std::io::Write`]
+    ///
+    /// 数据按固定大小的块编码后逐块写入，不会生成完整的编码字符串，适合很大的数据；
+    /// `w` 没有缓冲时建议包装为 [`std::io::BufWriter`]
+    pub fn write_io(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        let mut adapter = IoWrite { w, error: None };
+        match write!(adapter, "{}", self) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| std::io::Error::other("格式化 Data URL 失败"))),
+        }
+    }
+
+    /// 逗号之前的头部，如 `data:image/png;base64`
+    pub fn header(&self) -> String {
+        let mut header = String::new();
+        let _ = self.write_header(&mut header);
+        header
+    }
+
+    /// 逗号之后编码后的数据部分 (base64 或百分号编码)，不含片段
+    pub fn encoded_payload(&self) -> String {
+        let mut payload = String::new();
+        let _ = self.write_payload(&mut payload);
+        payload
+    }
+
+    /// 渲染为 CSS 的 `url("...")`，可直接用于 `background: ...`
+    ///
+    /// 媒体类型参数与片段中的引号、括号、反斜杠以 CSS 转义，换行等控制字符以 `\hex ` 形式转义
+    pub fn to_css_url(&self) -> String {
+        let mut css = String::from("url(\"");
+        for c in self.to_string().chars() {
+            match c {
+                '"' | '\'' | '(' | ')' | '\\' => {
+                    css.push('\\');
+                    css.push(c);
+                }
+                c if c.is_control() => {
+                    let _ = write!(css, "\\{:x} ", u32::from(c));
+                }
+                c => css.push(c),
+            }
+        }
+        css.push_str("\")");
+        css
+    }
+
+    /// 渲染为可放入 HTML 属性值 (如 `src="..."`) 的字符串，引号、括号、`&`、`<`、`>` 以字符引用转义
+    pub fn to_html_attr(&self) -> String {
+        let mut html = String::new();
+        for c in self.to_string().chars() {
+            match c {
+                '&' => html.push_str("&amp;"),
+                '"' => html.push_str("&quot;"),
+                '\'' => html.push_str("&#39;"),
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '(' => html.push_str("&#40;"),
+                ')' => html.push_str("&#41;"),
+                c => html.push(c),
+            }
+        }
+        html
+    }
+
+    fn write_header(&self, w: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let encoding = if self.data_url.base64_encoded {
+            ";base64"
+        } else {
+            ""
+        };
+        write!(w, "data:{}{}", self.media_type(), encoding)
+    }
+
+    /// 数据部分直接流式写入，不生成中间字符串
+    fn write_payload(&self, w: &mut impl std::fmt::Write) -> std::fmt::Result {
+        let data_url = self.data_url;
+        if data_url.base64_encoded {
+            let variant = if self.padding {
+                self.base64_variant
+            } else {
+                self.base64_variant.without_padding()
+            };
+            let base64 = Base64Display::new(&data_url.data, variant.engine());
+            match self.line_wrap {
+                Some(width) => write!(LineWrap::new(w, width), "{}", base64),
+                None => write!(w, "{}", base64),
+            }
+        } else {
+            // 对于非 base64 编码，需要确保数据是 URL 安全的
+            write!(w, "{}", percent_encode(&data_url.data, DATA_ENCODE_SET))
+        }
+    }
+
+    /// 实际输出的媒体类型
+    ///
+    /// 参数按 [`DataUrl`] 文档中的规范顺序输出，保证相同的输入总是得到相同的字符串；
+    /// 参数值中的 `%`、`,`、`#` 与空白以百分号编码，解析时还原
+    fn media_type(&self) -> Cow<'a, str> {
+        let media_type = &self.data_url.media_type;
+        if self.omit_default_media_type && is_default_media_type(media_type) {
+            Cow::Borrowed("")
+        } else {
+            params::encode_values(params::canonical_order(media_type))
+        }
+    }
+}
+
+impl Display for Render<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_header(f)?;
+        f.write_char(',')?;
+        self.write_payload(f)?;
+        match &self.data_url.fragment {
+            Some(fragment) => write!(f, "#{}", fragment),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 每写入 `width` 个字符插入一个换行的 [`std::fmt::Write`] 适配器，只用于 ASCII 内容
+struct LineWrap<'a, W> {
+    f: &'a mut W,
+    width: usize,
+    column: usize,
+}
+
+impl<'a, W: std::fmt::Write> LineWrap<'a, W> {
+    fn new(f: &'a mut W, width: usize) -> Self {
+        Self {
+            f,
+            width,
+            column: 0,
+        }
+    }
+}
+
+impl<W: std::fmt::Write> std::fmt::Write for LineWrap<'_, W> {
+    fn write_str(&mut self, mut s: &str) -> std::fmt::Result {
+        while !s.is_empty() {
+            if self.column == self.width {
+                self.f.write_str("\r\n")?;
+                self.column = 0;
+            }
+            let (line, rest) = s.split_at((self.width - self.column).min(s.len()));
+            self.f.write_str(line)?;
+            self.column += line.len();
+            s = rest;
+        }
+        Ok(())
+    }
+}
+
+/// 将 [`std::fmt::Write`] 转发到 [`std::io::Write`]，保留第一个 I/O 错误
+struct IoWrite<'a, W> {
+    w: &'a mut W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> std::fmt::Write for IoWrite<'_, W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.w.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
+/// 是否为 RFC 2397 的默认媒体类型 `text/plain;charset=US-ASCII`
+fn is_default_media_type(media_type: &str) -> bool {
+    media_type.parse::<Mime>().is_ok_and(|mime| {
+        mime.essence_str() == "text/plain"
+            && mime.params().count() == 1
+            && mime
+                .get_param(mime::CHARSET)
+                .is_some_and(|charset| charset.as_str().eq_ignore_ascii_case("us-ascii"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_wrap() {
+        let data: Vec<u8> = (0..=255).collect();
+        let data_url = DataUrl::new("application/octet-stream", data.clone(), true);
+
+        let wrapped = data_url.render().line_wrap(Some(76)).to_string();
+        let (header, payload) = wrapped.split_once(',').unwrap();
+        assert_eq!(header, "data:application/octet-stream;base64");
+        let lines: Vec<_> = payload.split("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= 76));
+        assert!(lines[..lines.len() - 1].iter().all(|line| line.len() == 76));
+
+        // 解析时忽略换行，数据保持不变
+        assert_eq!(DataUrl::parse(&wrapped).unwrap().data, data);
+
+        // 默认单行
+        assert!(!data_url.to_string().contains('\n'));
+        assert_eq!(
+            data_url.render().line_wrap(None).to_string(),
+            data_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_css_and_html() {
+        let mut data_url = DataUrl::new("image/svg+xml", b"<svg a='1'/>".to_vec(), false);
+        data_url.set_param("name", "a \"b\" (c)");
+        data_url.fragment = Some("it's".to_string());
+
+        let css = data_url.render().to_css_url();
+        let inner = css
+            .strip_prefix("url(\"")
+            .unwrap()
+            .strip_suffix("\")")
+            .unwrap();
+        for c in ['"', '\'', '(', ')'] {
+            assert!(
+                inner
+                    .match_indices(c)
+                    .all(|(i, _)| inner[..i].ends_with('\\')),
+                "{}",
+                css
+            );
+        }
+        assert!(inner.contains(r#"name=\"a%20\\\"b\\\"%20\(c\)\""#));
+
+        let html = data_url.render().to_html_attr();
+        assert!(!html.contains(['"', '\'', '(', ')', '<']), "{}", html);
+        assert!(html.contains(r"name=&quot;a%20\&quot;b\&quot;%20&#40;c&#41;&quot;"));
+        assert!(html.ends_with("#it&#39;s"));
+
+        // 百分号编码与 base64 的数据部分本身不含这些字符
+        let data_url = DataUrl::new("text/plain", b"f(\"x\")".to_vec(), false);
+        assert_eq!(
+            data_url.render().to_css_url(),
+            format!("url(\"{}\")", data_url)
+        );
+        assert_eq!(data_url.render().to_html_attr(), data_url.to_string());
+    }
+
+    #[test]
+    fn test_header_and_payload() {
+        let data_url = DataUrl::new("text/csv", b"a,b\n1,2".to_vec(), false);
+        assert_eq!(data_url.header(), "data:text/csv");
+        assert_eq!(data_url.encoded_payload(), "a%2Cb%0A1%2C2");
+
+        let mut data_url = DataUrl::new("image/png", b"Hello".to_vec(), true);
+        data_url.fragment = Some("frag".to_string());
+        assert_eq!(data_url.header(), "data:image/png;base64");
+        assert_eq!(data_url.encoded_payload(), "SGVsbG8=");
+        assert_eq!(
+            data_url.to_string(),
+            format!("{},{}#frag", data_url.header(), data_url.encoded_payload())
+        );
+    }
+
+    #[test]
+    fn test_param_order() {
+        // charset 在前，其余参数按名称字母顺序
+        let data_url = DataUrl::new("text/plain;name=a.txt;Charset=utf-8", b"Hi".to_vec(), false);
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;Charset=utf-8;name=a.txt,Hi"
+        );
+
+        let data_url = DataUrl::new("text/plain; b=2; a=1", b"Hi".to_vec(), false);
+        assert_eq!(data_url.to_string(), "data:text/plain;a=1;b=2,Hi");
+        assert_eq!(data_url.encoded_len(), data_url.to_string().len());
+
+        // 没有值的片段保留在排序后的参数之后
+        let data_url = DataUrl::new("text/plain;b=1;flag;a=2", b"Hi".to_vec(), false);
+        assert_eq!(data_url.to_string(), "data:text/plain;a=2;b=1;flag,Hi");
+        assert_eq!(data_url.encoded_len(), data_url.to_string().len());
+
+        // 已经是规范顺序时保持原样，包括空白
+        let data_url = DataUrl::new("text/html; charset=utf-8; a=1", b"Hi".to_vec(), false);
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/html; charset=utf-8; a=1,Hi"
+        );
+    }
+
+    #[test]
+    fn test_padding() {
+        let data_url = DataUrl::new("text/plain", b"Hi".to_vec(), true);
+        assert_eq!(data_url.to_string(), "data:text/plain;base64,SGk=");
+
+        let unpadded = data_url.render().padding(false).to_string();
+        assert_eq!(unpadded, "data:text/plain;base64,SGk");
+        assert_eq!(DataUrl::parse(&unpadded).unwrap(), data_url);
+
+        // 百分号编码不受影响
+        let data_url = data_url.to_percent();
+        assert_eq!(
+            data_url.render().padding(false).to_string(),
+            "data:text/plain,Hi"
+        );
+    }
+
+    #[test]
+    fn test_write_io() {
+        /// 记录最大的单次写入，确认数据是分块写入的
+        #[derive(Default)]
+        struct Sink {
+            written: Vec<u8>,
+            max_write: usize,
+        }
+
+        impl std::io::Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.max_write = self.max_write.max(buf.len());
+                self.written.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i * 7 % 251) as u8).collect();
+        for base64_encoded in [true, false] {
+            let data_url = DataUrl::new("application/octet-stream", data.clone(), base64_encoded);
+            let mut sink = Sink::default();
+            data_url.render().write_io(&mut sink).unwrap();
+            assert!(sink.max_write <= 4096, "{}", sink.max_write);
+            assert_eq!(sink.written, data_url.to_string().into_bytes());
+            assert_eq!(
+                DataUrl::parse(std::str::from_utf8(&sink.written).unwrap()).unwrap(),
+                data_url
+            );
+        }
+
+        struct Broken;
+
+        impl std::io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data_url = DataUrl::new("text/plain", b"Hi".to_vec(), true);
+        let error = data_url.render().write_io(&mut Broken).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_base64_variant() {
+        let data_url = DataUrl::new("application/octet-stream", vec![0xFB, 0xFF], true);
+        for (variant, padding, payload) in [
+            (Base64Variant::Standard, true, "+/8="),
+            (Base64Variant::StandardNoPad, true, "+/8"),
+            (Base64Variant::UrlSafe, true, "-_8="),
+            (Base64Variant::UrlSafeNoPad, true, "-_8"),
+            (Base64Variant::UrlSafe, false, "-_8"),
+        ] {
+            let render = data_url.render().base64_variant(variant).padding(padding);
+            assert_eq!(render.encoded_payload(), payload);
+            assert_eq!(DataUrl::parse(&render.to_string()).unwrap(), data_url);
+        }
+    }
+
+    #[test]
+    fn test_omit_default_media_type() {
+        let data_url = DataUrl::new("text/plain;charset=US-ASCII", b"Hi".to_vec(), true);
+        assert_eq!(
+            data_url.to_string(),
+            "data:text/plain;charset=US-ASCII;base64,SGk="
+        );
+        assert_eq!(
+            data_url.render().omit_default_media_type(true).to_string(),
+            "data:;base64,SGk="
+        );
+
+        let data_url = DataUrl::new("text/plain; charset=us-ascii", b"Hi".to_vec(), false);
+        assert_eq!(
+            data_url.render().omit_default_media_type(true).to_string(),
+            "data:,Hi"
+        );
+
+        // 非默认类型保持不变
+        let data_url = DataUrl::new("text/plain;charset=utf-8", b"Hi".to_vec(), false);
+        assert_eq!(
+            data_url.render().omit_default_media_type(true).to_string(),
+            "data:text/plain;charset=utf-8,Hi"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::sniff;
+
+/// 媒体类型的一个来源，见 [`ContentTypeResolver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentTypeSource {
+    /// 响应的 Content-Type，本地文件与读取器没有此来源
+    Header,
+    /// 根据 URL 路径或文件名的扩展名推断
+    Extension,
+    /// 根据数据开头的魔数推断，不受 [`GetDataUrl::with_sniffing`](crate::GetDataUrl::with_sniffing) 影响
+    Sniff,
+    /// 固定使用指定的媒体类型
+    Override(String),
+}
+
+/// 媒体类型的判定顺序，依次尝试各来源直到得到结果，都没有结果时使用默认媒体类型
+///
+/// 通过 [`GetDataUrl::with_content_type_resolver`](crate::GetDataUrl::with_content_type_resolver) 启用。
+/// 未设置时，响应使用 Content-Type，本地文件使用扩展名，启用嗅探时再根据数据推断
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentTypeResolver {
+    sources: Vec<ContentTypeSource>,
+}
+
+impl ContentTypeResolver {
+    /// 按给定顺序创建
+    pub fn new(sources: impl IntoIterator<Item = ContentTypeSource>) -> Self {
+        Self {
+            sources: sources.into_iter().collect(),
+        }
+    }
+
+    /// 判定顺序
+    pub fn sources(&self) -> &[ContentTypeSource] {
+        &self.sources
+    }
+
+    /// 依次尝试各来源
+    ///
+    /// `data` 为 `None` 表示尚未读取数据：轮到 [`ContentTypeSource::Sniff`] 时停止并返回 `None`，
+    /// 以便调用方在读取数据之前就能确定由响应头或覆盖值决定的类型
+    pub(crate) fn resolve(
+        &self,
+        header: Option<&str>,
+        path: Option<&str>,
+        data: Option<&[u8]>,
+    ) -> Option<String> {
+        for source in &self.sources {
+            let media_type = match source {
+                ContentTypeSource::Header => header.map(str::to_string),
+                ContentTypeSource::Extension => path
+                    .and_then(|path| mime_guess::from_path(path).first())
+                    .map(|mime| mime.to_string()),
+                ContentTypeSource::Sniff => sniff::sniff(data?).map(str::to_string),
+                ContentTypeSource::Override(media_type) => Some(media_type.clone()),
+            };
+            if media_type.is_some() {
+                return media_type;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_order() {
+        use ContentTypeSource::*;
+
+        let png = b"\x89PNG\r\n\x1a\n".as_slice();
+        let resolver = ContentTypeResolver::new([Header, Extension, Sniff]);
+        assert_eq!(
+            resolver.resolve(Some("image/webp"), Some("/a.svg"), Some(png)),
+            Some("image/webp".to_string())
+        );
+        assert_eq!(
+            resolver.resolve(None, Some("/a.svg"), Some(png)),
+            Some("image/svg+xml".to_string())
+        );
+        assert_eq!(
+            resolver.resolve(None, Some("/a"), Some(png)),
+            Some("image/png".to_string())
+        );
+        assert_eq!(resolver.resolve(None, None, Some(b"?")), None);
+
+        let resolver = ContentTypeResolver::new([Sniff, Override("text/plain".to_string())]);
+        assert_eq!(resolver.resolve(None, None, None), None);
+        assert_eq!(
+            resolver.resolve(None, None, Some(b"?")),
+            Some("text/plain".to_string())
+        );
+    }
+}
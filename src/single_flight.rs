@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::FutureExt as _;
+use futures::future::Shared;
+
+use crate::{DataUrl, DataUrlError};
+
+/// 装箱的请求 future，wasm32 上的 future 不是 `Send`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type Boxed<T> = futures::future::BoxFuture<'static, T>;
+#[cfg(target_arch = "wasm32")]
+pub(crate) type Boxed<T> = futures::future::LocalBoxFuture<'static, T>;
+
+/// 共享的进行中请求，错误包装在 `Arc` 中以便复制给每个调用者
+type Flight = Shared<Boxed<Result<DataUrl, Arc<DataUrlError>>>>;
+
+/// 合并对同一 URL 的并发请求，所有调用者共享同一次下载的结果
+#[derive(Default)]
+pub(crate) struct SingleFlight {
+    flights: Mutex<HashMap<String, Flight>>,
+}
+
+impl std::fmt::Debug for SingleFlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleFlight")
+            .field("in_flight", &self.flights.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl SingleFlight {
+    /// 存在相同 `key` 的进行中请求时等待其结果，否则调用 `fetch` 发起新请求
+    pub(crate) async fn run(
+        &self,
+        key: String,
+        fetch: impl FnOnce() -> Boxed<Result<DataUrl, DataUrlError>>,
+    ) -> Result<DataUrl, DataUrlError> {
+        let flight = self
+            .flights
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let flight: Boxed<_> = Box::pin(fetch().map(|result| result.map_err(Arc::new)));
+                flight.shared()
+            })
+            .clone();
+
+        let result = flight.clone().await;
+
+        // 只移除自己等待的那次请求，之后的新请求可能已经占用了同一个键
+        {
+            let mut flights = self.flights.lock().unwrap();
+            if flights
+                .get(&key)
+                .is_some_and(|current| current.ptr_eq(&flight))
+            {
+                flights.remove(&key);
+            }
+        }
+        drop(flight);
+
+        // 没有其他调用者持有该错误时取回原始错误，否则包装为 `Shared`
+        result.map_err(|e| Arc::try_unwrap(e).unwrap_or_else(DataUrlError::Shared))
+    }
+}
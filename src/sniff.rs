@@ -0,0 +1,62 @@
+//! 根据数据开头的魔数推断媒体类型
+
+/// 推断媒体类型时最多检查的字节数
+pub(crate) const SNIFF_LEN: usize = 1024;
+
+/// 根据数据开头的字节推断媒体类型，无法识别时返回 `None`
+pub(crate) fn sniff(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xFF\xD8\xFF", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+    ];
+
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+        .map(|(_, media_type)| *media_type)
+        .or_else(|| is_svg(data).then_some("image/svg+xml"))
+}
+
+/// SVG 没有固定魔数，检查开头 (忽略 BOM、空白和 XML 声明) 是否出现 `<svg` 标签
+fn is_svg(data: &[u8]) -> bool {
+    let head = &data[..data.len().min(SNIFF_LEN)];
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let head = head.trim_ascii_start();
+    (head.starts_with(b"<svg") || head.starts_with(b"<?xml") || head.starts_with(b"<!--"))
+        && head.windows(4).any(|window| window == b"<svg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_magic_numbers() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(sniff(b"\xFF\xD8\xFF\xE0\0\x10JFIF"), Some("image/jpeg"));
+        assert_eq!(sniff(b"GIF89a\x01\0\x01\0"), Some("image/gif"));
+        assert_eq!(sniff(b"%PDF-1.7\n"), Some("application/pdf"));
+    }
+
+    #[test]
+    fn test_sniff_svg() {
+        assert_eq!(
+            sniff(br#"<svg xmlns="http://www.w3.org/2000/svg"/>"#),
+            Some("image/svg+xml")
+        );
+        assert_eq!(
+            sniff(b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<svg/>"),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff(b"<?xml version=\"1.0\"?><html/>"), None);
+    }
+
+    #[test]
+    fn test_sniff_unknown() {
+        assert_eq!(sniff(b"Hello, World!"), None);
+        assert_eq!(sniff(b""), None);
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use tokio::io::AsyncRead;
+
+/// [`GetDataUrl::build`] 的数据来源
+///
+/// 无论来源如何，都遵循转换器的大小限制 (`max_size`)、编码策略与编码后长度限制
+///
+/// [`GetDataUrl::build`]: crate::GetDataUrl::build
+pub enum Source {
+    /// 通过 HTTP 获取，等同于 [`GetDataUrl::fetch`](crate::GetDataUrl::fetch)
+    Url(String),
+    /// 读取本地文件，等同于 [`GetDataUrl::from_path`](crate::GetDataUrl::from_path)
+    Path(PathBuf),
+    /// 已在内存中的数据，媒体类型由调用者给出
+    Bytes { data: Vec<u8>, media_type: String },
+    /// 读取到结束为止，媒体类型通过内容嗅探推断 (不受 `with_sniffing` 影响)，无法推断时使用默认媒体类型
+    Reader(Box<dyn AsyncRead + Send + Unpin>),
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Url(url) => f.debug_tuple("Url").field(url).finish(),
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Bytes { data, media_type } => f
+                .debug_struct("Bytes")
+                .field("len", &data.len())
+                .field("media_type", media_type)
+                .finish(),
+            Self::Reader(_) => f.debug_tuple("Reader").finish_non_exhaustive(),
+        }
+    }
+}